@@ -0,0 +1,219 @@
+// Decklist parsing shared by the migration tool and the server
+use serde::{Deserialize, Serialize};
+
+// One line of an MTG Arena decklist, section headers like "Commander" come through with no quantity
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DecklistEntry {
+    pub quantity: Option<i32>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CardInDeck {
+    pub quantity: Option<i32>,
+    pub name: String,
+    pub a_name: String,
+    pub is_commander: bool,
+    pub is_companion: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ArenaDecklist {
+    pub cards: Vec<CardInDeck>,
+    // Lines that aren't a section header or a `1 Card Name (SET) 123` card
+    pub unparsed: Vec<String>,
+}
+
+// Tags each card with the Commander/Companion/Deck section it's listed under
+// and rewrites names to how they're stored in the card table
+pub fn convert_decklist(decklist: Vec<DecklistEntry>) -> Vec<CardInDeck> {
+    let mut result = Vec::new();
+    let mut is_commander = false;
+    let mut is_companion = false;
+
+    for card in decklist {
+        if card.name == "Commander" {
+            is_commander = true;
+            is_companion = false;
+        } else if card.name == "Companion" {
+            is_companion = true;
+            is_commander = false;
+        } else if card.name == "Deck" {
+            is_commander = false;
+            is_companion = false;
+        } else if let Some((front, back)) = card.name.split_once(" /// ") {
+            let (front, back) = (
+                front.strip_prefix("A-").unwrap_or(front),
+                back.strip_prefix("A-").unwrap_or(back),
+            );
+            result.push(CardInDeck {
+                quantity: card.quantity,
+                name: format!("{} // {}", front, back),
+                a_name: format!("A-{} // A-{}", front, back),
+                is_commander,
+                is_companion,
+            })
+        } else {
+            let name = card
+                .name
+                .strip_prefix("A-")
+                .unwrap_or(&card.name)
+                .to_string();
+            result.push(CardInDeck {
+                quantity: card.quantity,
+                a_name: format!("A-{name}"),
+                name,
+                is_commander,
+                is_companion,
+            });
+        }
+    }
+    result
+}
+
+// Parses the text Arena copies to the clipboard when exporting a deck, eg.
+// Commander
+// 1 Krenko, Mob Boss (M13) 145
+//
+// Deck
+// 1 Goblin Bombardment (DMR) 127
+pub fn parse_arena_export(text: &str) -> ArenaDecklist {
+    let mut entries = Vec::new();
+    let mut unparsed = Vec::new();
+    // The "About" (deck name) and "Sideboard" sections aren't part of a Brawl deck
+    let mut skipping = false;
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match line {
+            "Commander" | "Companion" | "Deck" => {
+                skipping = false;
+                entries.push(DecklistEntry {
+                    quantity: None,
+                    name: line.to_string(),
+                });
+            }
+            "About" | "Sideboard" => skipping = true,
+            _ if skipping => (),
+            _ => match parse_arena_line(line) {
+                Some(entry) => entries.push(entry),
+                None => unparsed.push(line.to_string()),
+            },
+        }
+    }
+
+    ArenaDecklist {
+        cards: convert_decklist(entries),
+        unparsed,
+    }
+}
+
+// "1 Cut /// Ribbons (AKH) 223" -> 1, "Cut /// Ribbons"
+fn parse_arena_line(line: &str) -> Option<DecklistEntry> {
+    let (quantity, name) = line.split_once(' ')?;
+    let quantity: i32 = quantity.trim_end_matches('x').parse().ok()?;
+    let name = strip_set_and_collector_number(name.trim());
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(DecklistEntry {
+        quantity: Some(quantity),
+        name: name.to_string(),
+    })
+}
+
+// The set code and collector number are optional, and only stripped when they look like "(SET) 123"
+fn strip_set_and_collector_number(name: &str) -> &str {
+    let Some(set_start) = name.rfind(" (") else {
+        return name;
+    };
+    let Some((set, collector_number)) = name[set_start + 2..].split_once(')') else {
+        return name;
+    };
+
+    let is_set_code = !set.is_empty() && set.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let collector_number = collector_number.trim();
+    if is_set_code && !collector_number.contains(' ') {
+        name[..set_start].trim_end()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_strips_set_codes() {
+        let decklist = parse_arena_export(
+            "About\nName Goblins\n\nCommander\n1 Krenko, Mob Boss (M13) 141\n\nCompanion\n1 Lurrus of the Dream-Den (IKO) 226\n\nDeck\n1 Goblin Bombardment (DMR) 127\n20 Mountain\n\nSideboard\n1 Shock (M21) 159\n",
+        );
+
+        let cards: Vec<(&str, Option<i32>, bool, bool)> = decklist
+            .cards
+            .iter()
+            .map(|card| {
+                (
+                    card.name.as_str(),
+                    card.quantity,
+                    card.is_commander,
+                    card.is_companion,
+                )
+            })
+            .collect();
+        assert_eq!(
+            cards,
+            vec![
+                ("Krenko, Mob Boss", Some(1), true, false),
+                ("Lurrus of the Dream-Den", Some(1), false, true),
+                ("Goblin Bombardment", Some(1), false, false),
+                ("Mountain", Some(20), false, false),
+            ]
+        );
+        assert!(decklist.unparsed.is_empty());
+    }
+
+    #[test]
+    fn keeps_unparsed_lines() {
+        let decklist = parse_arena_export("Deck\nnot a card\n1 Shock\n");
+        assert_eq!(decklist.unparsed, vec!["not a card"]);
+        assert_eq!(decklist.cards.len(), 1);
+    }
+
+    #[test]
+    fn parses_quantity_with_x() {
+        let entry = parse_arena_line("4x Shock").unwrap();
+        assert_eq!(entry.quantity, Some(4));
+        assert_eq!(entry.name, "Shock");
+    }
+
+    #[test]
+    fn only_strips_what_looks_like_a_set_and_collector_number() {
+        assert_eq!(strip_set_and_collector_number("Shock (M21) 159"), "Shock");
+        assert_eq!(strip_set_and_collector_number("Shock"), "Shock");
+        assert_eq!(
+            strip_set_and_collector_number("Card (with parens) in name"),
+            "Card (with parens) in name"
+        );
+    }
+
+    #[test]
+    fn converts_split_and_alchemy_names() {
+        let cards = convert_decklist(vec![
+            DecklistEntry {
+                quantity: Some(1),
+                name: "Cut /// Ribbons".to_string(),
+            },
+            DecklistEntry {
+                quantity: Some(1),
+                name: "A-Skirk Prospector".to_string(),
+            },
+        ]);
+        assert_eq!(cards[0].name, "Cut // Ribbons");
+        assert_eq!(cards[0].a_name, "A-Cut // A-Ribbons");
+        assert_eq!(cards[1].name, "Skirk Prospector");
+        assert_eq!(cards[1].a_name, "A-Skirk Prospector");
+    }
+}
@@ -4,7 +4,7 @@ import { useRouter } from 'next/navigation'
 import { ChangeEvent, useEffect, useRef, useState } from 'react'
 import { fetchJsonFromPublic } from '../_utils/fetch-json'
 
-type SearchResults = { cardName: string; image: string; slug: string }
+type SearchResults = { cardName: string; image: string; slug: string; isCommander: boolean }
 export function Search({ className, overlayClass }: { className: string; overlayClass: string }) {
   const router = useRouter()
   const [searchQuery, setSearchQuery] = useState('')
@@ -1,3 +1,12 @@
+// Envelope returned by every paginated list endpoint
+export type Page<T> = {
+  total: number
+  page: number
+  per_page: number
+  next_page: number | null
+  items: T
+}
+
 export async function fetchJson<T>(url: RequestInfo, init?: RequestInit | undefined): Promise<T> {
   return (await fetch(`${url}`, init)).json()
 }
@@ -1,6 +1,6 @@
 import { TopCard, CardGridWithText } from '@/app/_components/card-grid'
 import { colorCombinations } from '@/app/_utils/color-combinations'
-import { fetchJsonFromBrawlhub } from '@/app/_utils/fetch-json'
+import { fetchJsonFromBrawlhub, Page as Paginated } from '@/app/_utils/fetch-json'
 
 export const dynamicParams = false
 export async function generateStaticParams() {
@@ -8,6 +8,8 @@ export async function generateStaticParams() {
 }
 
 export default async function Page({ params }: { params: { colorIdentity: string } }) {
-  const cardsOfColorIdentity = await fetchJsonFromBrawlhub<TopCard[]>(`top_cards/${params.colorIdentity}`)
+  const cardsOfColorIdentity = (
+    await fetchJsonFromBrawlhub<Paginated<TopCard[]>>(`top_cards/${params.colorIdentity}`)
+  ).items
   return <CardGridWithText cards={cardsOfColorIdentity} />
 }
@@ -1,8 +1,8 @@
 import { TopCard, CardGridWithText } from '../_components/card-grid'
 import React from 'react'
-import { fetchJsonFromBrawlhub } from '../_utils/fetch-json'
+import { fetchJsonFromBrawlhub, Page as Paginated } from '../_utils/fetch-json'
 
 export default async function Page() {
-  const topCards = await fetchJsonFromBrawlhub<TopCard[]>('top_cards')
+  const topCards = (await fetchJsonFromBrawlhub<Paginated<TopCard[]>>('top_cards')).items
   return <CardGridWithText cards={topCards} />
 }
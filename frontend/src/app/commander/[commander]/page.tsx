@@ -1,6 +1,6 @@
 import CardGrid, { Card } from '@/app/_components/card-grid'
 import { CommanderPage, CardSlug } from '@/app/_components/commander-page'
-import { fetchJsonFromBrawlhub } from '@/app/_utils/fetch-json'
+import { fetchJsonFromBrawlhub, Page as Paginated } from '@/app/_utils/fetch-json'
 import { ClickableChip } from '@/app/_components/clickable-chip'
 import Link from 'next/link'
 import React from 'react'
@@ -36,7 +36,9 @@ export async function generateStaticParams() {
 export default async function Page({ params }: { params: { commander: string } }) {
   const commanderSlug = params.commander
   const commanderCard = await fetchJsonFromBrawlhub<CardSlug>(`commander/${commanderSlug}`)
-  const topCards = await fetchJsonFromBrawlhub<TopCards>(`commander_top_cards/${commanderCard.oracle_id}`)
+  const topCards = (
+    await fetchJsonFromBrawlhub<Paginated<TopCards>>(`commander_top_cards/${commanderCard.oracle_id}`)
+  ).items
   const sections = [
     // ['Top Cards', ],
     // (TODO) replace the _ with a -
@@ -1,6 +1,6 @@
 import CardGrid, { CardCount } from '@/app/_components/card-grid'
 import { colorCombinations } from '@/app/_utils/color-combinations'
-import { fetchJsonFromBrawlhub } from '@/app/_utils/fetch-json'
+import { fetchJsonFromBrawlhub, Page as Paginated } from '@/app/_utils/fetch-json'
 
 export const dynamicParams = false
 export async function generateStaticParams() {
@@ -8,7 +8,9 @@ export async function generateStaticParams() {
 }
 
 export default async function Page({ params }: { params: { colorIdentity: string } }) {
-  const commandersOfColorIdentity = await fetchJsonFromBrawlhub<CardCount[]>(`commanders/${params.colorIdentity}`)
+  const commandersOfColorIdentity = (
+    await fetchJsonFromBrawlhub<Paginated<CardCount[]>>(`commanders/${params.colorIdentity}?per_page=1000`)
+  ).items
   return (
     <CardGrid cards={commandersOfColorIdentity} linkTo="commander">
       {CardText}
@@ -1,9 +1,9 @@
 import React from 'react'
 import CardGrid, { CardCount } from '../_components/card-grid'
-import { fetchJsonFromBrawlhub } from '../_utils/fetch-json'
+import { fetchJsonFromBrawlhub, Page as Paginated } from '../_utils/fetch-json'
 
 export default async function Page() {
-  const topCommanders = await fetchJsonFromBrawlhub<CardCount[]>('commanders/')
+  const topCommanders = (await fetchJsonFromBrawlhub<Paginated<CardCount[]>>('commanders/')).items
   return (
     <CardGrid cards={topCommanders} linkTo="commander">
       {CardText}
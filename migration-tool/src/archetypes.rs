@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+// Commanders with fewer decks than this aren't split into archetypes
+const MIN_DECKS: usize = 10;
+// Only the newest decks of a commander are clustered, the distance matrix grows with the square of the decks
+// The older ones join the archetype whose medoid they're closest to
+const MAX_CLUSTERED_DECKS: usize = 500;
+const MAX_ARCHETYPES: usize = 3;
+// Every archetype has to hold at least this share of the commander's decks
+const MIN_ARCHETYPE_SHARE: f64 = 0.15;
+// Average silhouette a split needs before it's kept, below it the decks are one archetype
+const MIN_SILHOUETTE: f64 = 0.1;
+const LABEL_CARDS: usize = 3;
+
+struct DeckCards {
+    id: i32,
+    cards: HashSet<Uuid>,
+}
+
+struct Archetype {
+    commander: Uuid,
+    archetype: i32,
+    label: String,
+    decks: Vec<i32>,
+}
+
+// Groups each commander's decks by how many cards they share, then stores the archetype of every deck
+// and a label made from the cards that set each archetype apart from the commander's other decks
+pub async fn populate_commander_archetypes(pool: &Pool<Postgres>) {
+    let rows = sqlx::query!(
+        "SELECT deck.id, deck.commander, decklist.oracle_id AS \"oracle_id!\"
+        FROM deck
+        JOIN decklist ON decklist.deck_id = deck.id
+        JOIN card ON card.oracle_id = decklist.oracle_id
+        WHERE NOT decklist.is_commander
+        AND card.type_line_front NOT LIKE 'Basic Land%'
+        AND deck.commander IN (
+            SELECT commander FROM deck GROUP BY commander HAVING COUNT(*) >= $1
+        )
+        ORDER BY deck.commander, deck.date_updated DESC, deck.id",
+        MIN_DECKS as i64
+    )
+    .fetch_all(pool)
+    .await
+    .expect("select decklists for archetypes");
+
+    let names: HashMap<Uuid, String> = sqlx::query!("SELECT oracle_id, name_front FROM card")
+        .fetch_all(pool)
+        .await
+        .expect("select card names")
+        .into_iter()
+        .map(|card| (card.oracle_id, card.name_front))
+        .collect();
+
+    let mut decks_per_commander: HashMap<Uuid, Vec<DeckCards>> = HashMap::new();
+    for row in rows {
+        let decks = decks_per_commander.entry(row.commander).or_default();
+        match decks.last_mut() {
+            Some(deck) if deck.id == row.id => {
+                deck.cards.insert(row.oracle_id);
+            }
+            _ => decks.push(DeckCards {
+                id: row.id,
+                cards: HashSet::from([row.oracle_id]),
+            }),
+        }
+    }
+
+    let archetypes: Vec<Archetype> = decks_per_commander
+        .into_iter()
+        .flat_map(|(commander, decks)| commander_archetypes(commander, &decks, &names))
+        .collect();
+
+    let mut transaction = pool.begin().await.expect("begin transaction");
+    sqlx::query!("UPDATE deck SET archetype = NULL WHERE archetype IS NOT NULL")
+        .execute(&mut *transaction)
+        .await
+        .expect("clear deck archetypes");
+    sqlx::query!("DELETE FROM commander_archetype")
+        .execute(&mut *transaction)
+        .await
+        .expect("clear commander_archetype");
+
+    let commanders: Vec<Uuid> = archetypes.iter().map(|a| a.commander).collect();
+    let numbers: Vec<i32> = archetypes.iter().map(|a| a.archetype).collect();
+    let labels: Vec<String> = archetypes.iter().map(|a| a.label.clone()).collect();
+    let deck_counts: Vec<i32> = archetypes.iter().map(|a| a.decks.len() as i32).collect();
+    sqlx::query!(
+        "INSERT INTO commander_archetype (commander, archetype, label, decks)
+        SELECT * FROM UNNEST($1::uuid[], $2::int[], $3::text[], $4::int[])",
+        &commanders,
+        &numbers,
+        &labels,
+        &deck_counts
+    )
+    .execute(&mut *transaction)
+    .await
+    .expect("insert into commander_archetype");
+
+    let (deck_ids, deck_archetypes): (Vec<i32>, Vec<i32>) = archetypes
+        .iter()
+        .flat_map(|a| a.decks.iter().map(|&deck| (deck, a.archetype)))
+        .unzip();
+    sqlx::query!(
+        "UPDATE deck SET archetype = archetypes.archetype
+        FROM UNNEST($1::int[], $2::int[]) AS archetypes(deck_id, archetype)
+        WHERE deck.id = archetypes.deck_id",
+        &deck_ids,
+        &deck_archetypes
+    )
+    .execute(&mut *transaction)
+    .await
+    .expect("update deck archetype");
+
+    transaction.commit().await.expect("commit archetypes");
+    println!(
+        "Updated commander_archetype with {} archetypes",
+        archetypes.len()
+    );
+}
+
+// Archetypes are numbered from 1, the most played one first
+fn commander_archetypes(
+    commander: Uuid,
+    decks: &[DeckCards],
+    names: &HashMap<Uuid, String>,
+) -> Vec<Archetype> {
+    // Decks are ordered newest first
+    let (sample, older) = decks.split_at(decks.len().min(MAX_CLUSTERED_DECKS));
+    let distances = distance_matrix(sample);
+    let min_size = (sample.len() as f64 * MIN_ARCHETYPE_SHARE).ceil() as usize;
+
+    let mut best: Option<(f64, Vec<usize>, Vec<usize>)> = None;
+    for k in 2..=MAX_ARCHETYPES.min(sample.len()) {
+        let (medoids, assignments) = k_medoids(&distances, k);
+        let too_small =
+            (0..k).any(|cluster| assignments.iter().filter(|&&a| a == cluster).count() < min_size);
+        if too_small {
+            continue;
+        }
+        let score = silhouette(&distances, &assignments, k);
+        let is_better = match &best {
+            Some((best, _, _)) => score > *best,
+            None => true,
+        };
+        if score >= MIN_SILHOUETTE && is_better {
+            best = Some((score, medoids, assignments));
+        }
+    }
+    let assignments: Vec<usize> = match best {
+        Some((_, medoids, mut assignments)) => {
+            assignments.extend(older.iter().map(|deck| {
+                (0..medoids.len())
+                    .map(|cluster| (cluster, distance(deck, &sample[medoids[cluster]])))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(cluster, _)| cluster)
+                    .unwrap_or_default()
+            }));
+            assignments
+        }
+        None => vec![0; decks.len()],
+    };
+
+    let mut clusters: Vec<Vec<&DeckCards>> = vec![];
+    for (deck, &cluster) in decks.iter().zip(&assignments) {
+        if clusters.len() <= cluster {
+            clusters.resize_with(cluster + 1, Vec::new);
+        }
+        clusters[cluster].push(deck);
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.len()));
+
+    clusters
+        .iter()
+        .enumerate()
+        .map(|(i, cluster)| {
+            let others: Vec<&DeckCards> = clusters
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, other)| other.iter().copied())
+                .collect();
+            Archetype {
+                commander,
+                archetype: i as i32 + 1,
+                label: label(cluster, &others, names),
+                decks: cluster.iter().map(|deck| deck.id).collect(),
+            }
+        })
+        .collect()
+}
+
+// Jaccard distance, 0 for decks with the same cards and 1 for decks without a card in common
+fn distance(a: &DeckCards, b: &DeckCards) -> f64 {
+    let shared = a.cards.intersection(&b.cards).count();
+    let union = a.cards.len() + b.cards.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        1.0 - shared as f64 / union as f64
+    }
+}
+
+fn distance_matrix(decks: &[DeckCards]) -> Vec<Vec<f64>> {
+    let mut distances = vec![vec![0.0; decks.len()]; decks.len()];
+    for i in 0..decks.len() {
+        for j in i + 1..decks.len() {
+            let distance = distance(&decks[i], &decks[j]);
+            distances[i][j] = distance;
+            distances[j][i] = distance;
+        }
+    }
+    distances
+}
+
+// The member with the smallest total distance to the others, each total is only computed once
+fn most_central(distances: &[Vec<f64>], members: &[usize]) -> Option<usize> {
+    members
+        .iter()
+        .map(|&i| (i, members.iter().map(|&j| distances[i][j]).sum::<f64>()))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+// Starts from the most central deck and the decks furthest from it, so the result is the same every run
+// Returns the medoid of every archetype and the archetype of every deck
+fn k_medoids(distances: &[Vec<f64>], k: usize) -> (Vec<usize>, Vec<usize>) {
+    let n = distances.len();
+
+    let everyone: Vec<usize> = (0..n).collect();
+    let mut medoids = vec![most_central(distances, &everyone).unwrap_or_default()];
+    while medoids.len() < k {
+        let furthest = (0..n)
+            .filter(|i| !medoids.contains(i))
+            .max_by(|&a, &b| {
+                let nearest = |i: usize| {
+                    medoids
+                        .iter()
+                        .map(|&m| distances[i][m])
+                        .fold(f64::MAX, f64::min)
+                };
+                nearest(a).total_cmp(&nearest(b))
+            })
+            .expect("more decks than archetypes");
+        medoids.push(furthest);
+    }
+
+    let mut assignments = vec![0; n];
+    for _ in 0..20 {
+        for (i, assignment) in assignments.iter_mut().enumerate() {
+            *assignment = (0..k)
+                .min_by(|&a, &b| distances[i][medoids[a]].total_cmp(&distances[i][medoids[b]]))
+                .unwrap_or_default();
+        }
+
+        let new_medoids: Vec<usize> = (0..k)
+            .map(|cluster| {
+                let members: Vec<usize> = (0..n).filter(|&i| assignments[i] == cluster).collect();
+                most_central(distances, &members).unwrap_or(medoids[cluster])
+            })
+            .collect();
+        if new_medoids == medoids {
+            break;
+        }
+        medoids = new_medoids;
+    }
+    (medoids, assignments)
+}
+
+// How much closer decks are to their own archetype than to the nearest other one, from -1 to 1
+fn silhouette(distances: &[Vec<f64>], assignments: &[usize], k: usize) -> f64 {
+    let n = distances.len();
+    let mean_distance = |i: usize, cluster: usize| -> Option<f64> {
+        let others: Vec<f64> = (0..n)
+            .filter(|&j| j != i && assignments[j] == cluster)
+            .map(|j| distances[i][j])
+            .collect();
+        (!others.is_empty()).then(|| others.iter().sum::<f64>() / others.len() as f64)
+    };
+
+    let total: f64 = (0..n)
+        .map(|i| {
+            let Some(own) = mean_distance(i, assignments[i]) else {
+                return 0.0;
+            };
+            let nearest = (0..k)
+                .filter(|&cluster| cluster != assignments[i])
+                .filter_map(|cluster| mean_distance(i, cluster))
+                .fold(f64::MAX, f64::min);
+            if nearest == f64::MAX || own.max(nearest) == 0.0 {
+                0.0
+            } else {
+                (nearest - own) / own.max(nearest)
+            }
+        })
+        .sum();
+    total / n as f64
+}
+
+// The cards played the most more often in this archetype than in the others, eg. "Goblin Bombardment, Skirk Prospector"
+// With a single archetype it's just the most played cards
+fn label(cluster: &[&DeckCards], others: &[&DeckCards], names: &HashMap<Uuid, String>) -> String {
+    let inclusion = |decks: &[&DeckCards]| -> HashMap<Uuid, f64> {
+        let mut counts: HashMap<Uuid, f64> = HashMap::new();
+        for deck in decks {
+            for card in &deck.cards {
+                *counts.entry(*card).or_default() += 1.0;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(card, count)| (card, count / decks.len().max(1) as f64))
+            .collect()
+    };
+    let in_cluster = inclusion(cluster);
+    let in_others = inclusion(others);
+
+    let mut cards: Vec<(&String, f64)> = in_cluster
+        .iter()
+        .filter_map(|(card, rate)| {
+            let name = names.get(card)?;
+            Some((
+                name,
+                rate - in_others.get(card).copied().unwrap_or_default(),
+            ))
+        })
+        .collect();
+    cards.sort_by(|(a_name, a), (b_name, b)| b.total_cmp(a).then(a_name.cmp(b_name)));
+
+    cards
+        .into_iter()
+        .take(LABEL_CARDS)
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
@@ -1,23 +1,39 @@
 #![allow(unused)]
+#![allow(
+    clippy::iter_nth_zero,
+    clippy::assign_op_pattern,
+    clippy::filter_map_identity,
+    clippy::unnecessary_unwrap
+)]
 // use postgres::{Client, NoTls};
+use decklist::{convert_decklist, DecklistEntry};
 use serde::{Deserialize, Serialize};
 use slug::slugify;
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 // use sqlx::types::Uuid;
 use chrono::prelude::*;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use dotenv::dotenv;
 use futures::future::join_all;
+use scryfall_bulk::{read_default_cards, ScryfallPrinting};
 use std::{
     collections::HashMap,
     fmt::Debug,
     fs::{self, remove_file, File},
-    io,
+    io::{self, Write},
     str::FromStr,
+    time::Instant,
 };
 use uuid::Uuid;
 
+mod archetypes;
+mod scryfall_bulk;
+
 #[tokio::main]
 async fn main() {
+    // Parsed before connecting, so --help and argument errors don't need a database
+    let cli = Cli::parse();
+
     dotenv().ok();
     let database_url = std::env::var("DATABASE_URL").expect("set DATABASE_URL env variable");
     let pool = PgPoolOptions::new()
@@ -26,98 +42,355 @@ async fn main() {
         .await
         .expect("couldn't connect to db");
 
-    update_default_cards().await;
-    migrate_scryfall_alchemy_cards(&pool).await;
-    populate_scryfall_id_table(&pool).await;
-    for decks in 0..10 {
-        println!("Decks {} - {} of Aetherhub", decks * 50, (decks + 1) * 50);
-        for deck in get_aetherhub_decks(decks * 50, 50).await {
-            migrate_aetherhub_decklists(&pool, &deck).await;
+    match cli.command {
+        Command::SyncCards { skip_download } => {
+            sync_cards(&pool, &cli.bulk_file, skip_download, cli.dry_run).await
+        }
+        Command::SyncDecks(decks) => sync_decks(&pool, &decks, cli.dry_run).await,
+        Command::RecomputeStats => recompute_stats(&pool, cli.dry_run).await,
+        Command::EnglishFix => english_fix(&pool, &cli.bulk_file, cli.dry_run).await,
+        Command::All(decks) => {
+            sync_cards(&pool, &cli.bulk_file, false, cli.dry_run).await;
+            sync_decks(&pool, &decks, cli.dry_run).await;
+            recompute_stats(&pool, cli.dry_run).await;
         }
     }
-    populate_total_decks_per_card_table(&pool).await;
-    populate_total_decks_per_color_identity_table(&pool).await;
 }
 
-async fn update_cards_to_english(pool: &Pool<Postgres>) -> Result<(), Box<dyn std::error::Error>> {
+#[derive(Parser)]
+#[command(
+    about = "Updates the Brawlhub database with cards from Scryfall and decks from deck sites"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Scryfall default cards bulk file, downloaded by sync-cards and read by sync-cards and english-fix
+    #[arg(long, global = true, default_value = "default-cards.json")]
+    bulk_file: String,
+    /// Fetch and parse everything, but only print what would change instead of writing to the database
+    #[arg(long, global = true)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Download the Scryfall bulk file and update cards, scryfall ids and printings
+    SyncCards {
+        /// Read the bulk file already on disk instead of downloading a new one
+        #[arg(long)]
+        skip_download: bool,
+    },
+    /// Fetch the newest decks of every format from a deck site
+    SyncDecks(DeckArgs),
+    /// Recompute card totals, commander similarity, archetypes and top cards from the decks in the database
+    RecomputeStats,
+    /// Update cards in the database to their newest English printing from the bulk file
+    EnglishFix,
+    /// sync-cards, sync-decks and recompute-stats, the weekly update
+    All(DeckArgs),
+}
+
+#[derive(Args)]
+struct DeckArgs {
+    #[arg(long, value_enum, default_value_t = Source::Aetherhub)]
+    source: Source,
+    /// Pages of decks to fetch for each format. Moxfield pages until the last run's newest deck instead,
+    /// and only uses this when it has no cursor yet
+    #[arg(long, default_value_t = 10)]
+    pages: i32,
+    /// Decks on each page
+    #[arg(long, default_value_t = 50)]
+    page_size: i32,
+}
 
-    let data = fs::read_to_string("default-cards.json").expect("unable to read JSON");
-    let scryfall_cards: Vec<ScryfallCard> =
-        serde_json::from_str(&data).expect("unable to parse JSON");
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Source {
+    Aetherhub,
+    Moxfield,
+}
 
-    // rarity order: common < uncommon < rare < mythic
-    fn is_lower_rarity(current: &str, new: &str) -> bool {
-        match (current, new) {
-            ("common", _) => false,
-            ("uncommon", "common") => true,
-            ("uncommon", _) => false,
-            ("rare", "common") | ("rare", "uncommon") => true,
-            ("rare", _) => false,
-            ("mythic", "rare") | ("mythic", "uncommon") | ("mythic", "common") => true,
-            _ => false,
+impl Source {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Source::Aetherhub => "aetherhub",
+            Source::Moxfield => "moxfield",
         }
     }
+}
 
-    let unwanted_layouts = [
-        "token",
-        "flip",
-        "planar",
-        "scheme",
-        "vanguard",
-        "double_faced_token",
-        "emblem",
-        "augment",
-        "host",
-        "art_series",
-        "reversible_card",
-    ];
-
-    let scryfall_cards: Vec<ScryfallCard> = scryfall_cards
-        .into_iter()
-        .filter(|card| {
-            card.games().contains(&String::from("arena"))
-                && !unwanted_layouts.contains(&card.layout().as_str())
-                && card.lang() == "en" // Filter to only English printings
-        })
-        .collect();
+async fn sync_cards(pool: &Pool<Postgres>, bulk_file: &str, skip_download: bool, dry_run: bool) {
+    // A dry run reads the bulk file already on disk, so it doesn't replace it either
+    if !skip_download && !dry_run {
+        update_default_cards(bulk_file).await;
+    }
+    let default_cards = read_default_cards(bulk_file);
+
+    if dry_run {
+        let oracle_ids: Vec<Uuid> = default_cards
+            .cards
+            .keys()
+            .map(|oracle_id| Uuid::parse_str(oracle_id).expect("Parse uuid from oracle_id string"))
+            .collect();
+        let existing = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM card WHERE oracle_id = ANY($1)"#,
+            &oracle_ids
+        )
+        .fetch_one(pool)
+        .await
+        .expect("count existing cards");
+        println!(
+            "Would update {} cards, {} of them new, and {} printings",
+            oracle_ids.len(),
+            oracle_ids.len() as i64 - existing,
+            default_cards.printings.len()
+        );
+        return;
+    }
 
-    let mut unique_scryfall_cards: HashMap<String, ScryfallCard> = HashMap::new();
+    migrate_scryfall_alchemy_cards(pool, &default_cards.cards).await;
+    populate_scryfall_id_table(pool, &default_cards.printings).await;
+    populate_printing_table(pool, &default_cards.printings).await;
+}
 
-    scryfall_cards.into_iter().for_each(|mut card| {
-        card.set_lowest_rarity(card.rarity());
-        if card.rarity() != card.lowest_rarity() {
-            println!("rarities don't match");
-        }
-        let (oracle_id, released_at) = (card.oracle_id(), card.released_at());
-        let new_released_at = released_at;
-
-        unique_scryfall_cards
-            .entry(oracle_id.clone())
-            .and_modify(|existing_card| {
-                if existing_card.released_at() < new_released_at {
-                    *existing_card = card.clone();
+async fn sync_decks(pool: &Pool<Postgres>, args: &DeckArgs, dry_run: bool) {
+    match args.source {
+        Source::Aetherhub => sync_aetherhub_decks(pool, args, dry_run).await,
+        Source::Moxfield => sync_moxfield_decks(pool, args, dry_run).await,
+    }
+}
+
+// Pages through the most recently updated decks until reaching the newest deck of the last successful run,
+// stored in sync_state. Without a cursor, eg. on the first run, only `--pages` pages are fetched
+// Decks that fail to import keep the cursor behind them, so they're fetched again on the next run,
+// decks that can never be imported are skipped. A search page that fails leaves the cursor where it was
+async fn sync_moxfield_decks(pool: &Pool<Postgres>, args: &DeckArgs, dry_run: bool) {
+    for format in [Format::Historic, Format::Standard] {
+        let cursor = sqlx::query_scalar!(
+            "SELECT cursor FROM sync_state WHERE source = $1 AND format = $2",
+            Source::Moxfield.as_str(),
+            format.as_str()
+        )
+        .fetch_optional(pool)
+        .await
+        .expect("select moxfield cursor");
+
+        let mut newest_updated = cursor;
+        let mut oldest_failed: Option<i64> = None;
+        let mut search_failed = false;
+        let (mut new_decks, mut updated_decks, mut skipped_decks, mut failed_decks) = (0, 0, 0, 0);
+        let mut page = 1;
+        loop {
+            if cursor.is_none() && page > args.pages {
+                break;
+            }
+            if page > 1 {
+                tokio::time::sleep(MOXFIELD_REQUEST_DELAY).await;
+            }
+            println!("Page {page} of Moxfield {}", format.as_str());
+            let Some(decks) = get_moxfield_decks(format, page, args.page_size).await else {
+                search_failed = true;
+                break;
+            };
+            if decks.is_empty() {
+                break;
+            }
+
+            let mut reached_cursor = false;
+            for deck in &decks {
+                let date_updated = deck.last_updated_at_utc.timestamp_millis();
+                if cursor.is_some_and(|cursor| date_updated <= cursor) {
+                    reached_cursor = true;
+                    break;
                 }
+                newest_updated = newest_updated.max(Some(date_updated));
 
-                // Update lowest_rarity if it's empty or the new card has a lower rarity
-                if is_lower_rarity(&existing_card.lowest_rarity(), &card.rarity()) {
-                    existing_card.set_lowest_rarity(card.rarity());
+                let existing = sqlx::query_scalar!(
+                    "SELECT date_updated FROM deck WHERE mox_deck_id = $1",
+                    deck.id
+                )
+                .fetch_optional(pool)
+                .await
+                .expect("select existing moxfield deck");
+                if existing == Some(date_updated) {
+                    continue;
                 }
-            })
-            .or_insert(card);
-    });
+                if !dry_run {
+                    tokio::time::sleep(MOXFIELD_REQUEST_DELAY).await;
+                    match migrate_moxfield_decklists(pool, deck, format).await {
+                        MoxfieldImport::Imported => (),
+                        MoxfieldImport::Skipped => {
+                            skipped_decks += 1;
+                            continue;
+                        }
+                        MoxfieldImport::Failed => {
+                            failed_decks += 1;
+                            oldest_failed = Some(date_updated);
+                            continue;
+                        }
+                    }
+                }
+                match existing {
+                    Some(_) => updated_decks += 1,
+                    None => new_decks += 1,
+                }
+            }
 
-    // Get a list of all the alchemy card names, with the 'A-' prefix stripped, and remove cards with that name from the HashMap
-    // println!("Cards: {}", unique_scryfall_cards.len());
+            if reached_cursor {
+                break;
+            }
+            page += 1;
+        }
 
-    let alchemy_card_names: Vec<String> = unique_scryfall_cards
-        .iter()
-        .filter_map(|(_, card)| {
-            card.is_rebalanced()
-                .then(|| strip_alchemy_prefix(&card.name()))
-        })
-        .collect();
-    unique_scryfall_cards.retain(|_, card| !alchemy_card_names.contains(&card.name().to_string()));
+        if dry_run {
+            println!(
+                "Would add {new_decks} new and update {updated_decks} changed Moxfield {} decks",
+                format.as_str()
+            );
+            continue;
+        }
+        println!(
+            "Added {new_decks} new and updated {updated_decks} changed Moxfield {} decks, skipped {skipped_decks} and {failed_decks} failed",
+            format.as_str()
+        );
+
+        // Decks come newest first, so the last failure is the oldest one
+        let next_cursor = if search_failed {
+            cursor
+        } else {
+            oldest_failed.map(|failed| failed - 1).or(newest_updated)
+        };
+        if let Some(next_cursor) = next_cursor {
+            sqlx::query!(
+                "INSERT INTO sync_state (source, format, cursor, date_updated)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (source, format) DO UPDATE SET
+                    cursor = EXCLUDED.cursor,
+                    date_updated = EXCLUDED.date_updated",
+                Source::Moxfield.as_str(),
+                format.as_str(),
+                next_cursor,
+                Utc::now().timestamp_millis()
+            )
+            .execute(pool)
+            .await
+            .expect("update moxfield cursor");
+        }
+    }
+}
+
+async fn sync_aetherhub_decks(pool: &Pool<Postgres>, args: &DeckArgs, dry_run: bool) {
+    for format in [Format::Historic, Format::Standard] {
+        for page in 0..args.pages {
+            println!(
+                "Decks {} - {} of Aetherhub {}",
+                page * args.page_size,
+                (page + 1) * args.page_size,
+                format.as_str()
+            );
+            let decks = get_aetherhub_decks(format, page * args.page_size, args.page_size).await;
+
+            if dry_run {
+                let ah_deck_ids: Vec<i32> = decks.iter().map(|deck| deck.id).collect();
+                let existing = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) AS "count!" FROM deck WHERE ah_deck_id = ANY($1)"#,
+                    &ah_deck_ids
+                )
+                .fetch_one(pool)
+                .await
+                .expect("count existing decks");
+                println!(
+                    "Would add {} new decks of {}",
+                    decks.len() as i64 - existing,
+                    decks.len()
+                );
+                continue;
+            }
+
+            for deck in decks {
+                migrate_aetherhub_decklists(pool, &deck, format).await;
+            }
+        }
+    }
+}
+
+async fn recompute_stats(pool: &Pool<Postgres>, dry_run: bool) {
+    if dry_run {
+        let decks = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM deck"#)
+            .fetch_one(pool)
+            .await
+            .expect("count decks");
+        println!("Would recompute stats from {decks} decks");
+        return;
+    }
+
+    populate_total_decks_per_card_table(pool).await;
+    populate_commander_similarity_table(pool).await;
+    archetypes::populate_commander_archetypes(pool).await;
+    populate_total_decks_per_color_identity_table(pool).await;
+    populate_top_cards_table(pool).await;
+}
+
+async fn english_fix(pool: &Pool<Postgres>, bulk_file: &str, dry_run: bool) {
+    let default_cards = read_default_cards(bulk_file);
+
+    if dry_run {
+        let oracle_ids: Vec<Uuid> = default_cards
+            .cards
+            .keys()
+            .map(|oracle_id| Uuid::parse_str(oracle_id).expect("Parse uuid from oracle_id string"))
+            .collect();
+        let not_english = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM card WHERE oracle_id = ANY($1) AND lang <> 'en'"#,
+            &oracle_ids
+        )
+        .fetch_one(pool)
+        .await
+        .expect("count cards that aren't English");
+        println!("Would update {not_english} cards that aren't English");
+        return;
+    }
+
+    update_cards_to_english(pool, &default_cards.cards)
+        .await
+        .expect("update cards to English");
+    println!("Updated cards to English");
+}
+
+// Brawl format a deck was built for, stored in deck.format
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Standard,
+    Historic,
+}
 
+impl Format {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Format::Standard => "standard",
+            Format::Historic => "historic",
+        }
+    }
+
+    // formatId of the format's meta list on Aetherhub, Standard Brawl is listed as "Brawl"
+    fn aetherhub_format_id(&self) -> i32 {
+        match self {
+            Format::Standard => 13,
+            Format::Historic => 19,
+        }
+    }
+
+    fn moxfield_fmt(&self) -> &'static str {
+        match self {
+            Format::Standard => "standardBrawl",
+            Format::Historic => "historicBrawl",
+        }
+    }
+}
+
+async fn update_cards_to_english(
+    pool: &Pool<Postgres>,
+    unique_scryfall_cards: &HashMap<String, ScryfallCard>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Step 1: Fetch all cards from the database
     let database_cards = sqlx::query_as!(Card,
         r#"
@@ -163,7 +436,7 @@ async fn update_cards_to_english(pool: &Pool<Postgres>) -> Result<(), Box<dyn st
     Ok(())
 }
 
-async fn update_default_cards() -> () {
+async fn update_default_cards(bulk_file: &str) -> () {
     #[derive(Serialize, Deserialize, Debug, Clone)]
     struct Response {
         object: String,
@@ -217,210 +490,195 @@ async fn update_default_cards() -> () {
         }
     };
 
+    // Written to disk as it downloads, the whole file is never held in memory
     let download_uri = json.download_uri.as_str();
     let mut res = reqwest::get(download_uri)
         .await
-        .expect("send GET request for download_uri")
-        .text()
-        .await
-        .expect("serialize default-cards.json as text");
+        .expect("send GET request for download_uri");
+    let new_bulk_file = format!("{bulk_file}.new");
     let mut out =
-        File::create("new-default-cards.json").expect("create new-default-cards.json file");
-    io::copy(&mut res.as_bytes(), &mut out).expect("copy default-cards.json to file");
-    match remove_file("default-cards.json") {
-        Ok(_) => println!("Old default-cards.json removed"),
-        Err(_) => println!("No previous default-cards.json found"),
+        File::create(&new_bulk_file).unwrap_or_else(|e| panic!("create {new_bulk_file} file: {e}"));
+    while let Some(chunk) = res.chunk().await.expect("download default-cards.json") {
+        out.write_all(&chunk)
+            .unwrap_or_else(|e| panic!("write {new_bulk_file}: {e}"));
     }
-    std::fs::rename("new-default-cards.json", "default-cards.json")
-        .expect("rename new-default-cards.json to default-cards.json");
+    match remove_file(bulk_file) {
+        Ok(_) => println!("Old {bulk_file} removed"),
+        Err(_) => println!("No previous {bulk_file} found"),
+    }
+    std::fs::rename(&new_bulk_file, bulk_file)
+        .unwrap_or_else(|e| panic!("rename {new_bulk_file} to {bulk_file}: {e}"));
 
-    println!("Updated default-cards.json");
+    println!("Updated {bulk_file}");
 }
 
 async fn populate_total_decks_per_card_table(pool: &Pool<Postgres>) {
-    let cards = sqlx::query!("SELECT DISTINCT oracle_id FROM card")
-        .fetch_all(pool)
-        .await
-        .expect("fetch all oracle_ids");
-
-    let mut count = 0;
-
-    for card in &cards {
-        count = count + 1;
-        // println!("COUNT {}", count);
-        let oracle_id = card.oracle_id;
-
-        let total_decks: i32 = sqlx::query_scalar!(
-            "SELECT COUNT(DISTINCT deck_id) FROM decklist WHERE oracle_id = $1",
-            oracle_id
-        )
-        .fetch_one(pool)
-        .await
-        .expect("get count of decks with card")
-        .expect("get count of decks with card")
-        .try_into()
-        .unwrap();
+    let start = Instant::now();
+    let cards = sqlx::query!(
+        "INSERT INTO total_decks_per_card (oracle_id, total_decks)
+        SELECT card.oracle_id, COUNT(DISTINCT decklist.deck_id)
+        FROM card
+        LEFT JOIN decklist ON decklist.oracle_id = card.oracle_id
+        GROUP BY card.oracle_id
+        ON CONFLICT (oracle_id) DO UPDATE SET total_decks = EXCLUDED.total_decks"
+    )
+    .execute(pool)
+    .await
+    .expect("insert into total_decks_per_card")
+    .rows_affected();
+    println!(
+        "Updated total_decks_per_card with {cards} cards in {:.2?}",
+        start.elapsed()
+    );
+}
 
-        sqlx::query!(
-            "INSERT INTO total_decks_per_card (oracle_id, total_decks) VALUES ($1, $2)
-            ON CONFLICT (oracle_id) DO UPDATE SET total_decks = $2",
-            oracle_id,
-            total_decks
-        )
+// Ranks every card by the share of decks that could play it that do, from total_decks_per_card and
+// total_decks_with_color_identity. The ranking is built in top_cards_staging first and copied over in
+// one transaction, so top_cards is never empty or half built while the server reads it
+async fn populate_top_cards_table(pool: &Pool<Postgres>) {
+    sqlx::query!("TRUNCATE TABLE top_cards_staging")
         .execute(pool)
         .await
-        .expect("insert into total_decks_per_card");
-    }
-    println!("Updated total_decks_per_card with {} cards", cards.len());
-}
+        .expect("clear top_cards_staging");
 
-async fn populate_total_decks_per_color_identity_table(pool: &Pool<Postgres>) {
-    let color_identities = vec![
-        vec![],
-        vec!["B"],
-        vec!["G"],
-        vec!["R"],
-        vec!["U"],
-        vec!["W"],
-        vec!["B", "G"],
-        vec!["B", "R"],
-        vec!["B", "U"],
-        vec!["B", "W"],
-        vec!["G", "R"],
-        vec!["G", "U"],
-        vec!["G", "W"],
-        vec!["R", "U"],
-        vec!["R", "W"],
-        vec!["U", "W"],
-        vec!["B", "G", "R"],
-        vec!["B", "G", "U"],
-        vec!["B", "G", "W"],
-        vec!["B", "R", "U"],
-        vec!["B", "R", "W"],
-        vec!["B", "U", "W"],
-        vec!["G", "R", "U"],
-        vec!["G", "R", "W"],
-        vec!["G", "U", "W"],
-        vec!["R", "U", "W"],
-        vec!["B", "G", "R", "U"],
-        vec!["B", "G", "R", "W"],
-        vec!["B", "G", "U", "W"],
-        vec!["B", "R", "U", "W"],
-        vec!["G", "R", "U", "W"],
-        vec!["B", "G", "R", "U", "W"],
-    ];
-
-    for color_identity in color_identities {
-        // let color_identity: Vec<String> = row.color_identity.iter().map(|s| s.clone()).collect();
-        // println!("{:#?}", color_identity);
-
-        let color_identity: Vec<String> = color_identity.iter().map(|s| s.to_string()).collect();
-
-        let total_decks: i32 = sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM deck WHERE color_identity::text[] @> $1",
-            &color_identity
-        )
-        .fetch_one(pool)
-        .await
-        .expect("get number of decks of color_identity")
-        .expect("get number of decks of color_identity")
-        .try_into()
-        .unwrap();
+    let staged = sqlx::query!(
+        "INSERT INTO top_cards_staging
+            (oracle_id, name_full, color_identity, total_decks_could_play, total_decks_with_card, rank)
+        SELECT
+            card.oracle_id,
+            card.name_full,
+            card.color_identity,
+            tdwci.total_decks,
+            tdpc.total_decks,
+            CASE
+                WHEN tdwci.total_decks = 0 THEN 0
+                ELSE tdpc.total_decks * 100.0 / tdwci.total_decks
+            END
+        FROM card
+        JOIN total_decks_per_card tdpc ON card.oracle_id = tdpc.oracle_id
+        JOIN total_decks_with_color_identity tdwci ON card.color_identity = tdwci.color_identity"
+    )
+    .execute(pool)
+    .await
+    .expect("insert into top_cards_staging")
+    .rows_affected();
 
-        sqlx::query!(
-            "INSERT INTO total_decks_with_color_identity (color_identity, total_decks) VALUES ($1, $2)
-            ON CONFLICT (color_identity) DO UPDATE SET total_decks = $2",
-            &color_identity,
-            total_decks
-        )
-        .execute(pool)
+    let mut transaction = pool.begin().await.expect("begin transaction");
+    sqlx::query!("DELETE FROM top_cards")
+        .execute(&mut *transaction)
         .await
-        .expect("insert into total_decks_with_color_identity");
-    }
-    println!("Updated total_decks_with_color_identity table")
-}
-
-async fn migrate_scryfall_alchemy_cards(pool: &Pool<Postgres>) {
-    let data = fs::read_to_string("default-cards.json").expect("unable to read JSON");
-    let scryfall_cards: Vec<ScryfallCard> =
-        serde_json::from_str(&data).expect("unable to parse JSON");
-
-    // rarity order: common < uncommon < rare < mythic
-    fn is_lower_rarity(current: &str, new: &str) -> bool {
-        match (current, new) {
-            ("common", _) => false,
-            ("uncommon", "common") => true,
-            ("uncommon", _) => false,
-            ("rare", "common") | ("rare", "uncommon") => true,
-            ("rare", _) => false,
-            ("mythic", "rare") | ("mythic", "uncommon") | ("mythic", "common") => true,
-            _ => false,
-        }
-    }
-
-    let unwanted_layouts = [
-        "token",
-        "flip",
-        "planar",
-        "scheme",
-        "vanguard",
-        "double_faced_token",
-        "emblem",
-        "augment",
-        "host",
-        "art_series",
-        "reversible_card",
-    ];
-
-    let scryfall_cards: Vec<ScryfallCard> = scryfall_cards
-        .into_iter()
-        .filter(|card| {
-            card.games().contains(&String::from("arena"))
-                && !unwanted_layouts.contains(&card.layout().as_str())
-                && card.lang() == "en" // Filter to only English printings
-        })
-        .collect();
+        .expect("clear top_cards");
+    sqlx::query!("INSERT INTO top_cards SELECT * FROM top_cards_staging")
+        .execute(&mut *transaction)
+        .await
+        .expect("copy top_cards_staging into top_cards");
+    transaction.commit().await.expect("commit top_cards");
 
-    let mut unique_scryfall_cards: HashMap<String, ScryfallCard> = HashMap::new();
+    println!("Updated top_cards with {staged} cards");
+}
 
-    scryfall_cards.into_iter().for_each(|mut card| {
-        card.set_lowest_rarity(card.rarity());
-        if card.rarity() != card.lowest_rarity() {
-            println!("rarities don't match");
-        }
-        let (oracle_id, released_at) = (card.oracle_id(), card.released_at());
-        let new_released_at = released_at;
-
-        unique_scryfall_cards
-            .entry(oracle_id.clone())
-            .and_modify(|existing_card| {
-                if existing_card.released_at() < new_released_at {
-                    *existing_card = card.clone();
-                }
+// Cosine similarity between commanders, over how often each card is played in that commander's decks
+// Only commanders with at least 5 decks are compared, and only the 50 most similar are kept for each
+async fn populate_commander_similarity_table(pool: &Pool<Postgres>) {
+    let mut transaction = pool.begin().await.expect("begin transaction");
 
-                // Update lowest_rarity if it's empty or the new card has a lower rarity
-                if is_lower_rarity(&existing_card.lowest_rarity(), &card.rarity()) {
-                    existing_card.set_lowest_rarity(card.rarity());
-                }
-            })
-            .or_insert(card);
-    });
+    sqlx::query!("DELETE FROM commander_similarity")
+        .execute(&mut *transaction)
+        .await
+        .expect("clear commander_similarity");
+
+    let inserted = sqlx::query!(
+        "WITH CommanderDecks AS (
+            SELECT commander, COUNT(*) AS total_decks
+            FROM deck
+            GROUP BY commander
+            HAVING COUNT(*) >= 5
+        ),
+        Inclusion AS (
+            SELECT
+                deck.commander,
+                decklist.oracle_id,
+                COUNT(DISTINCT deck.id)::float / cd.total_decks AS rate
+            FROM decklist
+            JOIN deck ON deck.id = decklist.deck_id
+            JOIN CommanderDecks cd ON cd.commander = deck.commander
+            JOIN card ON card.oracle_id = decklist.oracle_id
+            WHERE NOT decklist.is_commander
+            AND card.type_line_front NOT LIKE 'Basic Land%'
+            GROUP BY deck.commander, decklist.oracle_id, cd.total_decks
+        ),
+        Norms AS (
+            SELECT commander, sqrt(SUM(rate * rate)) AS norm
+            FROM Inclusion
+            GROUP BY commander
+        ),
+        Similarities AS (
+            SELECT
+                a.commander,
+                b.commander AS similar_commander,
+                SUM(a.rate * b.rate) / (na.norm * nb.norm) AS similarity,
+                COUNT(*) AS shared_cards
+            FROM Inclusion a
+            JOIN Inclusion b ON b.oracle_id = a.oracle_id AND b.commander <> a.commander
+            JOIN Norms na ON na.commander = a.commander
+            JOIN Norms nb ON nb.commander = b.commander
+            GROUP BY a.commander, b.commander, na.norm, nb.norm
+        ),
+        Ranked AS (
+            SELECT *, row_number() OVER (PARTITION BY commander ORDER BY similarity DESC) AS rank
+            FROM Similarities
+        )
+        INSERT INTO commander_similarity (commander, similar_commander, similarity, shared_cards)
+        SELECT commander, similar_commander, similarity, shared_cards
+        FROM Ranked
+        WHERE rank <= 50"
+    )
+    .execute(&mut *transaction)
+    .await
+    .expect("insert into commander_similarity")
+    .rows_affected();
 
-    // Get a list of all the alchemy card names, with the 'A-' prefix stripped, and remove cards with that name from the HashMap
-    // println!("Cards: {}", unique_scryfall_cards.len());
+    transaction.commit().await.expect("commit commander_similarity");
+    println!("Updated commander_similarity with {inserted} pairs");
+}
 
-    let alchemy_card_names: Vec<String> = unique_scryfall_cards
-        .iter()
-        .filter_map(|(_, card)| {
-            card.is_rebalanced()
-                .then(|| strip_alchemy_prefix(&card.name()))
-        })
-        .collect();
-    unique_scryfall_cards.retain(|_, card| !alchemy_card_names.contains(&card.name().to_string()));
+// Every combination of colors, from colorless to WUBRG, and how many decks could play a card of it
+async fn populate_total_decks_per_color_identity_table(pool: &Pool<Postgres>) {
+    let start = Instant::now();
+    let color_identities = sqlx::query!(
+        "WITH ColorIdentities AS (
+            SELECT COALESCE(
+                array_agg(color ORDER BY color) FILTER (WHERE mask & (1 << (position - 1)::int) <> 0),
+                '{}'
+            )::char(1)[] AS color_identity
+            FROM generate_series(0, 31) AS mask
+            CROSS JOIN unnest(ARRAY['B', 'G', 'R', 'U', 'W']) WITH ORDINALITY AS colors(color, position)
+            GROUP BY mask
+        )
+        INSERT INTO total_decks_with_color_identity (color_identity, total_decks)
+        SELECT ColorIdentities.color_identity, COUNT(deck.id)
+        FROM ColorIdentities
+        LEFT JOIN deck ON deck.color_identity @> ColorIdentities.color_identity
+        GROUP BY ColorIdentities.color_identity
+        ON CONFLICT (color_identity) DO UPDATE SET total_decks = EXCLUDED.total_decks"
+    )
+    .execute(pool)
+    .await
+    .expect("insert into total_decks_with_color_identity")
+    .rows_affected();
+    println!(
+        "Updated total_decks_with_color_identity with {color_identities} color identities in {:.2?}",
+        start.elapsed()
+    );
+}
 
+async fn migrate_scryfall_alchemy_cards(
+    pool: &Pool<Postgres>,
+    unique_scryfall_cards: &HashMap<String, ScryfallCard>,
+) {
     let cards: Vec<Card> = unique_scryfall_cards
-        .into_iter()
-        .map(|(oracle_id, scryfall_card)| Card::from(scryfall_card))
+        .values()
+        .map(|scryfall_card| Card::from(scryfall_card.clone()))
         .collect();
 
     for card in cards {
@@ -465,15 +723,18 @@ async fn migrate_scryfall_alchemy_cards(pool: &Pool<Postgres>) {
             image_large_back,
             image_art_crop_back,
             image_border_crop_back,
-            lowest_rarity
+            lowest_rarity,
+            is_legal_standard,
+            arena_id
         )
         VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35)
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37)
         ON CONFLICT (oracle_id) DO UPDATE SET
             scryfall_uri = EXCLUDED.scryfall_uri,
             rarity = EXCLUDED.rarity,
             lang = EXCLUDED.lang,
             is_legal = EXCLUDED.is_legal,
+            is_legal_standard = EXCLUDED.is_legal_standard,
             is_legal_commander = EXCLUDED.is_legal_commander,
             is_rebalanced = EXCLUDED.is_rebalanced,
             image_small = EXCLUDED.image_small,
@@ -486,7 +747,8 @@ async fn migrate_scryfall_alchemy_cards(pool: &Pool<Postgres>) {
             image_large_back = EXCLUDED.image_large_back,
             image_art_crop_back = EXCLUDED.image_art_crop_back,
             image_border_crop_back = EXCLUDED.image_border_crop_back,
-            lowest_rarity = EXCLUDED.lowest_rarity
+            lowest_rarity = EXCLUDED.lowest_rarity,
+            arena_id = EXCLUDED.arena_id
         ",
         Uuid::parse_str(&card.oracle_id).expect("Parse uuid from oracle_id string"),
         card.name_full,
@@ -523,6 +785,8 @@ async fn migrate_scryfall_alchemy_cards(pool: &Pool<Postgres>) {
         card.image_art_crop_back,
         card.image_border_crop_back,
         card.lowest_rarity,
+        card.is_legal_standard,
+        card.arena_id,
         )
         .execute(pool)
         .await
@@ -532,16 +796,10 @@ async fn migrate_scryfall_alchemy_cards(pool: &Pool<Postgres>) {
     println!("All new cards added to database")
 }
 
-async fn populate_scryfall_id_table(pool: &Pool<Postgres>) -> () {
-    #[derive(Deserialize)]
-    struct ScryfallId {
-        id: String,
-        oracle_id: Option<String>,
-    }
-
-    let data = fs::read_to_string("default-cards.json").expect("unable to read JSON");
-    let scryfall_ids: Vec<ScryfallId> = serde_json::from_str(&data).expect("unable to parse JSON");
-
+async fn populate_scryfall_id_table(
+    pool: &Pool<Postgres>,
+    scryfall_ids: &[ScryfallPrinting],
+) -> () {
     let mut count = 0;
 
     for id in scryfall_ids {
@@ -573,6 +831,78 @@ async fn populate_scryfall_id_table(pool: &Pool<Postgres>) -> () {
     println!("Populated ScryfallID table");
 }
 
+// Every printing of the cards in the card table, which only keeps the newest one
+// Arena exports and collections refer to cards by a printing's set and collector number or its arena_id
+async fn populate_printing_table(pool: &Pool<Postgres>, printings: &[ScryfallPrinting]) {
+    let printings: Vec<&ScryfallPrinting> = printings
+        .iter()
+        .filter(|printing| printing.oracle_id.is_some())
+        .collect();
+
+    // Inserted a chunk at a time as arrays, one round trip per printing takes minutes
+    let mut count = 0;
+    for chunk in printings.chunks(5000) {
+        let mut scryfall_ids = vec![];
+        let mut oracle_ids = vec![];
+        let mut arena_ids = vec![];
+        let mut set_codes = vec![];
+        let mut set_names = vec![];
+        let mut collector_numbers = vec![];
+        let mut released_ats = vec![];
+        let mut rarities = vec![];
+        let mut langs = vec![];
+        for printing in chunk {
+            let oracle_id = printing.oracle_id.as_deref().unwrap_or_default();
+            scryfall_ids.push(Uuid::parse_str(&printing.id).expect("Parse uuid from scryfall id"));
+            oracle_ids.push(Uuid::parse_str(oracle_id).expect("Parse uuid from oracle_id string"));
+            arena_ids.push(printing.arena_id);
+            set_codes.push(printing.set.clone());
+            set_names.push(printing.set_name.clone());
+            collector_numbers.push(printing.collector_number.clone());
+            released_ats.push(printing.released_at.clone());
+            rarities.push(printing.rarity.clone());
+            langs.push(printing.lang.clone());
+        }
+
+        count += sqlx::query!(
+            "INSERT INTO printing (
+                scryfall_id, oracle_id, arena_id, set_code, set_name, collector_number, released_at, rarity, lang
+            )
+            SELECT printing.scryfall_id, printing.oracle_id, printing.arena_id, printing.set_code,
+                printing.set_name, printing.collector_number, printing.released_at::date, printing.rarity,
+                printing.lang
+            FROM UNNEST(
+                $1::uuid[], $2::uuid[], $3::int[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[], $9::text[]
+            ) AS printing(
+                scryfall_id, oracle_id, arena_id, set_code, set_name, collector_number, released_at, rarity, lang
+            )
+            WHERE EXISTS(SELECT 1 FROM card WHERE card.oracle_id = printing.oracle_id)
+            ON CONFLICT (scryfall_id) DO UPDATE SET
+                arena_id = EXCLUDED.arena_id,
+                set_code = EXCLUDED.set_code,
+                set_name = EXCLUDED.set_name,
+                collector_number = EXCLUDED.collector_number,
+                released_at = EXCLUDED.released_at,
+                rarity = EXCLUDED.rarity,
+                lang = EXCLUDED.lang",
+            &scryfall_ids,
+            &oracle_ids,
+            &arena_ids as &[Option<i32>],
+            &set_codes,
+            &set_names,
+            &collector_numbers,
+            &released_ats,
+            &rarities,
+            &langs
+        )
+        .execute(pool)
+        .await
+        .expect("couldn't insert printing")
+        .rows_affected();
+    }
+    println!("Populated printing table with {count} printings");
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct MoxfieldDeck {
@@ -594,7 +924,18 @@ struct User {
     user_name: String,
 }
 
-async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck) {
+// Skipped decks can never be imported, eg. without a commander, failed ones are fetched again on the next run
+enum MoxfieldImport {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+async fn migrate_moxfield_decklists(
+    pool: &Pool<Postgres>,
+    deck: &MoxfieldDeck,
+    format: Format,
+) -> MoxfieldImport {
     #[derive(Serialize, Deserialize, Debug, Clone)]
     struct MoxfieldCardInfo {
         quantity: i32,
@@ -640,26 +981,41 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
 
     let request_url = format!("https://api2.moxfield.com/v3/decks/all/{}", deck.public_id);
     // println!("{}", request_url);
-    let decklist = reqwest::Client::new()
+    let response = reqwest::Client::new()
         .get(request_url)
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .expect("couldn't send get request for moxfield deck")
-        .text()
-        .await
-        .expect("couldn't read reponse body");
+        .await;
+    let decklist = match response {
+        Ok(response) => response.text().await,
+        Err(err) => Err(err),
+    };
+    let decklist = match decklist {
+        Ok(decklist) => decklist,
+        Err(err) => {
+            eprintln!("Couldn't fetch Moxfield deck {}: {:?}", deck.public_id, err);
+            return MoxfieldImport::Failed;
+        }
+    };
 
     let json: Result<Response, _> = serde_json::from_str(&decklist);
     let json = match json {
         Ok(json) => json,
         Err(err) => {
             eprintln!("Couldn't deserialize JSON: {:?}", err);
-            return; // Exit the function early with an error message
+            return MoxfieldImport::Failed; // Exit the function early with an error message
         }
     };
 
-    let mainboard = json.boards.mainboard.cards;
+    // The commander and companion are part of the decklist, like Aetherhub decks
+    let mainboard: Vec<(String, MoxfieldCardInfo)> = json
+        .boards
+        .mainboard
+        .cards
+        .into_iter()
+        .chain(json.boards.commanders.cards.clone())
+        .chain(json.boards.companions.cards.clone())
+        .collect();
 
     struct IdQuantity {
         id: Uuid,
@@ -723,7 +1079,7 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
 
     if commander_id.is_none() {
         println!("no commander in commander board, skipping");
-        return;
+        return MoxfieldImport::Skipped;
     }
 
     let commander_id = commander_id
@@ -748,7 +1104,7 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
     .await;
 
     if commander_id.is_err() {
-        return;
+        return MoxfieldImport::Skipped;
     }
 
     let commander_id = commander_id
@@ -757,17 +1113,21 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
         .expect("no oracle_id for commander");
 
     let companion_id = if companion_id.is_some() {
-        Some(
-            sqlx::query!(
-                "SELECT oracle_id FROM scryfall_id WHERE scryfall_id = $1",
-                Uuid::from_str(&companion_id.unwrap()).expect("uuid from str")
-            )
-            .fetch_one(pool)
-            .await
-            .expect("couln't fetch oracle_id of commander")
-            .oracle_id
-            .expect("no oracle_id for commander"),
+        let companion = sqlx::query!(
+            "SELECT oracle_id FROM scryfall_id WHERE scryfall_id = $1",
+            Uuid::from_str(&companion_id.unwrap()).expect("uuid from str")
         )
+        .fetch_optional(pool)
+        .await
+        .expect("select oracle_id of companion")
+        .and_then(|companion| companion.oracle_id);
+        match companion {
+            Some(companion) => Some(companion),
+            None => {
+                eprintln!("Companion of Moxfield deck {} not found", deck.public_id);
+                return MoxfieldImport::Failed;
+            }
+        }
     } else {
         None
     };
@@ -795,6 +1155,7 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
         companion: Option<Uuid>,
         color_identity: Vec<String>,
         source: String,
+        format: String,
     }
 
     let insert = Deck {
@@ -808,28 +1169,43 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
         companion: companion_id,
         color_identity: color_identity.color_identity,
         source: String::from("moxfield"),
+        format: format.as_str().to_string(),
     };
 
+    let mut transaction = pool.begin().await.expect("begin transaction");
     sqlx::query_as!(
         Deck,
         "INSERT INTO deck 
-            (url, username, date_created, date_updated, commander, companion, color_identity, mox_deck_id, source)
+            (url, username, date_created, date_updated, commander, companion, color_identity, mox_deck_id, source, format)
         VALUES
-            ($1, $2, $3, $4, $5, $6, $7, $8, 'moxfield')
-        ON CONFLICT (mox_deck_id) DO NOTHING
+            ($1, $2, $3, $4, $5, $6, $7, $8, 'moxfield', $9)
+        ON CONFLICT (mox_deck_id) DO UPDATE SET
+            url = EXCLUDED.url,
+            username = EXCLUDED.username,
+            date_updated = EXCLUDED.date_updated,
+            commander = EXCLUDED.commander,
+            companion = EXCLUDED.companion,
+            color_identity = EXCLUDED.color_identity,
+            format = EXCLUDED.format
         ",
-        insert.url, insert.username, insert.date_created, insert.date_updated, insert.commander, insert.companion, &insert.color_identity, insert.mox_deck_id
-    ).execute(pool).await.expect("insert mox deck into db");
+        insert.url, insert.username, insert.date_created, insert.date_updated, insert.commander, insert.companion, &insert.color_identity, insert.mox_deck_id, insert.format
+    ).execute(&mut *transaction).await.expect("insert mox deck into db");
 
     let deck_id = sqlx::query!(
         "SELECT id FROM deck WHERE mox_deck_id = $1",
         insert.mox_deck_id
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *transaction)
     .await
     .expect("couldn't fetch deck_id")
     .id;
 
+    // A deck that changed on Moxfield gets its whole decklist replaced
+    sqlx::query!("DELETE FROM decklist WHERE deck_id = $1", deck_id)
+        .execute(&mut *transaction)
+        .await
+        .expect("delete old decklist of mox deck");
+
     for card in oracle_ids {
         let is_companion = Some(card.id) == insert.companion;
         let is_commander = card.id == insert.commander;
@@ -847,61 +1223,68 @@ async fn migrate_moxfield_decklists(pool: &Pool<Postgres>, deck: &MoxfieldDeck)
             is_commander,
             card.quantity
         )
-        .execute(pool)
+        .execute(&mut *transaction)
         .await
         .expect("insert decklist into db");
     }
+    transaction.commit().await.expect("commit mox deck");
     // println!("Moxfield Deck {} Inserted", deck_id);
+    MoxfieldImport::Imported
 }
 
-async fn get_moxfield_decks(page: i32) -> Vec<MoxfieldDeck> {
-    let request_url = format!("https://api2.moxfield.com/v2/decks/search-sfw?pageNumber={page}&pageSize=64&sortType=created&sortDirection=Descending&fmt=historicBrawl");
+// Moxfield rate limits its API, so requests after the first search page wait this long
+const MOXFIELD_REQUEST_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Newest updated first, so decks that changed since the last run are fetched again
+// None when the page couldn't be fetched, eg. when Moxfield rate limits the request
+async fn get_moxfield_decks(format: Format, page: i32, page_size: i32) -> Option<Vec<MoxfieldDeck>> {
+    let request_url = format!("https://api2.moxfield.com/v2/decks/search-sfw?pageNumber={page}&pageSize={page_size}&sortType=updated&sortDirection=Descending&fmt={}", format.moxfield_fmt());
 
     #[derive(Deserialize, Debug, Clone)]
     struct Response {
         data: Vec<MoxfieldDeck>,
     }
 
-    let res = reqwest::Client::new()
+    let res = match reqwest::Client::new()
         .get(request_url)
         .header("Content-Type", "application/json")
         .send()
         .await
-        .expect("couldn't send get request")
-        .text()
-        .await
-        .expect("couldn't read response body");
+    {
+        Ok(res) => res.text().await,
+        Err(err) => Err(err),
+    };
+    let res = match res {
+        Ok(res) => res,
+        Err(err) => {
+            eprintln!("Couldn't fetch Moxfield search page {page}: {:?}", err);
+            return None;
+        }
+    };
 
-    let json: Response = serde_json::from_str(&res).expect("deserialize moxfield res");
-    json.data
-        .into_iter()
-        .filter(|res| res.visibility != "deleted")
-        .collect()
+    let json: Response = match serde_json::from_str(&res) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!("Couldn't deserialize Moxfield search page {page}: {:?}", err);
+            return None;
+        }
+    };
+    Some(
+        json.data
+            .into_iter()
+            .filter(|res| res.visibility != "deleted")
+            .collect(),
+    )
 }
 
-async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck) {
-    #[derive(Serialize, Deserialize, Debug)]
-    struct AetherhubCard {
-        quantity: Option<i32>,
-        name: String,
-    }
-
-    #[derive(Serialize, Deserialize, Debug)]
-    struct CardInDeck {
-        quantity: Option<i32>,
-        name: String,
-        a_name: String,
-        is_commander: bool,
-        is_companion: bool,
-    }
-
+async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck, format: Format) {
     #[derive(Serialize, Deserialize, Debug)]
     #[serde(rename_all = "camelCase")]
     struct Response {
-        converted_deck: Vec<AetherhubCard>,
+        converted_deck: Vec<DecklistEntry>,
     }
 
-    let aetherhub_decklist: Vec<AetherhubCard> = serde_json::from_str::<Response>(
+    let aetherhub_decklist: Vec<DecklistEntry> = serde_json::from_str::<Response>(
         reqwest::Client::new()
             .get(format!(
                 "https://aetherhub.com/Deck/FetchMtgaDeckJson?deckId={}",
@@ -918,69 +1301,10 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
     .expect("couldn't parse aetherhub aetherhub_decklist response")
     .converted_deck
     .into_iter()
-    .filter_map(|card| {
-        if card.name != "" {
-            Some(AetherhubCard {
-                quantity: card.quantity,
-                name: card.name,
-            })
-        } else {
-            None
-        }
-    })
+    .filter(|card| !card.name.is_empty())
     .collect();
 
-    fn convert_aetherhub_decklist(decklist: Vec<AetherhubCard>) -> Vec<CardInDeck> {
-        let mut result = Vec::new();
-        let mut is_commander = false;
-        let mut is_companion = false;
-
-        for card in decklist {
-            if card.name == "Commander" {
-                is_commander = true;
-                is_companion = false;
-            } else if card.name == "Companion" {
-                is_companion = true;
-                is_commander = false;
-            } else if card.name == "Deck" {
-                is_commander = false;
-                is_companion = false;
-            } else if card.name.contains(" /// ") {
-                let (front, back): (&str, &str) = card.name.split_once(" /// ").unwrap();
-                let (front, back) = (
-                    front.strip_prefix("A-").unwrap_or(front),
-                    back.strip_prefix("A-").unwrap_or(back),
-                );
-                let (name, a_name) = (
-                    format!("{} // {}", front, back),
-                    format!("A-{} // A-{}", front, back),
-                );
-                result.push(CardInDeck {
-                    quantity: card.quantity,
-                    name: name.clone(),
-                    a_name: a_name.clone(),
-                    is_commander,
-                    is_companion,
-                })
-            } else {
-                let name = card
-                    .name
-                    .strip_prefix("A-")
-                    .unwrap_or(&card.name)
-                    .to_string();
-                result.push(CardInDeck {
-                    quantity: card.quantity,
-                    name: name.clone(),
-                    a_name: format!("A-{name}"),
-                    is_commander,
-                    is_companion,
-                });
-            }
-        }
-        result
-    }
-
-    let aetherhub_decklist = convert_aetherhub_decklist(aetherhub_decklist);
+    let aetherhub_decklist = convert_decklist(aetherhub_decklist);
 
     // Always search for alchemy version first, if not, then search for non-alchemy version
     // DFC cards do not have the '// Back Half'
@@ -996,13 +1320,14 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
             oracle_id: Uuid,
             name_full: String,
             color_identity: Vec<String>,
+            is_legal_standard: bool,
         }
 
         // println!("Card: {}", card.name);
 
         let result = sqlx::query_as!(
             OracleId,
-            "SELECT name_full, oracle_id, color_identity 
+            "SELECT name_full, oracle_id, color_identity, is_legal_standard
             FROM card
             WHERE unaccent(name_full) = unaccent($1)
             OR (unaccent(name_front) = unaccent($1) AND layout IN ('transform','modal_dfc', 'adventure')
@@ -1020,6 +1345,7 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
                 is_companion: card.is_companion,
                 quantity: card.quantity,
                 color_identity: res.color_identity,
+                is_legal_standard: res.is_legal_standard,
             })
         } else {
             eprintln!("Error for card {}, couldn't find oracle_id", card.name);
@@ -1039,6 +1365,7 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
         is_companion: bool,
         quantity: Option<i32>,
         color_identity: Vec<String>,
+        is_legal_standard: bool,
     }
 
     struct DeckID {
@@ -1059,12 +1386,25 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
         .find(|card| card.is_commander)
         .unwrap();
 
+    // Guards the Standard Brawl list against decks of another format, they're up to 60 cards with a Standard legal commander
+    if format == Format::Standard {
+        let deck_size: i32 = combined_card_data
+            .iter()
+            .filter(|card| !card.is_companion)
+            .map(|card| card.quantity.unwrap_or(1))
+            .sum();
+        if deck_size > 60 || !commander_info.is_legal_standard {
+            eprintln!("Deck {} isn't a Standard Brawl deck, skipping.", deck.id);
+            return;
+        }
+    }
+
     let companion = combined_card_data.iter().find(|card| card.is_companion);
 
     sqlx::query_as!(
         AetherHubDeck,
-        "INSERT INTO deck (id, ah_deck_id, url, username, date_created, date_updated, commander, color_identity, companion)
-        VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8)
+        "INSERT INTO deck (id, ah_deck_id, url, username, date_created, date_updated, commander, color_identity, companion, format)
+        VALUES (DEFAULT, $1, $2, $3, $4, $5, $6, $7, $8, $9)
         ON CONFLICT (ah_deck_id) DO NOTHING",
         // Uuid::parse_str(&deck.id).expect("uuid parsed wrong"),
         deck.id,
@@ -1076,6 +1416,7 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
         // companion.unwrap_or(Null),
         &commander_info.color_identity,
         companion.map(|c| c.oracle_id),
+        format.as_str(),
     )
     .execute(pool)
     .await
@@ -1116,7 +1457,8 @@ async fn migrate_aetherhub_decklists(pool: &Pool<Postgres>, deck: &AetherHubDeck
     // println!("Aetherhub Deck {} inserted", deck_id.id);
 }
 
-async fn get_aetherhub_decks(start: i32, length: i32) -> Vec<AetherHubDeck> {
+async fn get_aetherhub_decks(format: Format, start: i32, length: i32) -> Vec<AetherHubDeck> {
+    let format_id = format.aetherhub_format_id();
     let mut request_data: String = String::from(
         r#"
       {
@@ -1242,7 +1584,9 @@ async fn get_aetherhub_decks(start: i32, length: i32) -> Vec<AetherHubDeck> {
     request_data.push_str(&length);
 
     let res = reqwest::Client::new()
-        .post("https://aetherhub.com/Meta/FetchMetaListAdv?formatId=19")
+        .post(format!(
+            "https://aetherhub.com/Meta/FetchMetaListAdv?formatId={format_id}"
+        ))
         .header("Content-Type", "application/json")
         .body(request_data)
         .send()
@@ -1296,6 +1640,7 @@ struct Card {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1309,6 +1654,7 @@ struct Card {
     image_art_crop_back: Option<String>,
     image_border_crop_back: Option<String>,
     lowest_rarity: String,
+    arena_id: Option<i32>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -2624,6 +2970,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2638,6 +2985,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Split(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2661,6 +3009,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2675,6 +3024,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Flip(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2698,6 +3048,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: c.colors.clone(),
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2712,6 +3063,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Transform(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2735,6 +3087,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: c.card_faces[1].colors.clone(),
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2749,6 +3102,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: Some(c.card_faces[1].image_uris.border_crop.clone()),
                 image_border_crop_back: Some(c.card_faces[1].image_uris.art_crop.clone()),
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::ModalDFC(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2772,6 +3126,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: c.card_faces[1].colors.clone(),
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2786,6 +3141,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: Some(c.card_faces[1].image_uris.border_crop.clone()),
                 image_border_crop_back: Some(c.card_faces[1].image_uris.art_crop.clone()),
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Meld(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2809,13 +3165,14 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line)
                     && c.all_parts
                         .iter()
                         .find(|part| part.name == c.name())
                         .expect("Find Meld part associated to this card")
                         .component
-                        != "meld_result".to_string(),
+                        != "meld_result",
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
                 image_small: c.image_uris.small.clone(),
@@ -2829,6 +3186,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Leveler(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2852,6 +3210,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2866,6 +3225,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Class(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2889,6 +3249,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2903,6 +3264,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Saga(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2926,6 +3288,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2940,6 +3303,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Adventure(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -2963,6 +3327,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.card_faces[1].type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -2977,6 +3342,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Mutate(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3000,6 +3366,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3014,6 +3381,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Prototype(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3037,6 +3405,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3051,6 +3420,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Planar(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3074,6 +3444,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3088,6 +3459,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Scheme(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3111,6 +3483,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3125,6 +3498,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Vanguard(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3148,6 +3522,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3162,6 +3537,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Token(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3185,6 +3561,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3199,6 +3576,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::DoubleFacedToken(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3222,6 +3600,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: false,
+                is_legal_standard: false,
                 is_legal_commander: false,
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3236,6 +3615,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: Some(c.card_faces[1].image_uris.border_crop.clone()),
                 image_border_crop_back: Some(c.card_faces[1].image_uris.art_crop.clone()),
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Emblem(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3259,6 +3639,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3273,6 +3654,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Augment(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3296,6 +3678,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3310,6 +3693,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Host(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3333,6 +3717,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3347,6 +3732,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::ArtSeries(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3370,6 +3756,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: false,
+                is_legal_standard: false,
                 is_legal_commander: false,
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3414,6 +3801,7 @@ impl From<ScryfallCard> for Card {
                     |uris| uris.border_crop.clone(),
                 )),
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::ReversibleCard(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3437,6 +3825,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: false,
+                is_legal_standard: false,
                 is_legal_commander: false,
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3451,6 +3840,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: Some(c.card_faces[1].image_uris.border_crop.clone()),
                 image_border_crop_back: Some(c.card_faces[1].image_uris.art_crop.clone()),
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
             ScryfallCard::Case(c) => Card {
                 oracle_id: c.oracle_id(),
@@ -3474,6 +3864,7 @@ impl From<ScryfallCard> for Card {
                 colors_back: None,
                 color_identity: c.color_identity.clone(),
                 is_legal: c.legalities.brawl == "legal",
+                is_legal_standard: c.legalities.standardbrawl == "legal",
                 is_legal_commander: is_legal_commander(&c.type_line),
                 is_rebalanced: c.is_rebalanced(),
                 rarity: c.rarity.clone(),
@@ -3488,6 +3879,7 @@ impl From<ScryfallCard> for Card {
                 image_art_crop_back: None,
                 image_border_crop_back: None,
                 lowest_rarity: c.lowest_rarity.clone(),
+                arena_id: c.arena_id,
             },
         }
     }
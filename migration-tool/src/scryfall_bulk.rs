@@ -0,0 +1,134 @@
+use std::{collections::HashMap, fmt, fs::File, io::BufReader};
+
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::Value;
+
+use crate::{strip_alchemy_prefix, ScryfallCard, ScryfallCardProperties};
+
+const UNWANTED_LAYOUTS: [&str; 11] = [
+    "token",
+    "flip",
+    "planar",
+    "scheme",
+    "vanguard",
+    "double_faced_token",
+    "emblem",
+    "augment",
+    "host",
+    "art_series",
+    "reversible_card",
+];
+
+// Everything the migration uses from Scryfall's default cards bulk file
+pub struct DefaultCards {
+    // The newest English Arena printing of each card by oracle_id, without cards that have an alchemy version
+    pub cards: HashMap<String, ScryfallCard>,
+    // Every printing with an oracle_id, in any language and from any game
+    pub printings: Vec<ScryfallPrinting>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScryfallPrinting {
+    pub id: String,
+    pub oracle_id: Option<String>,
+    pub arena_id: Option<i32>,
+    pub set: String,
+    pub set_name: String,
+    pub collector_number: String,
+    pub released_at: String,
+    pub rarity: String,
+    pub lang: String,
+}
+
+// The bulk file is a few GB once parsed, so it's read one card at a time instead of as a Vec
+pub fn read_default_cards(path: &str) -> DefaultCards {
+    let file = File::open(path).unwrap_or_else(|e| panic!("unable to open {path}: {e}"));
+    let mut cards: HashMap<String, ScryfallCard> = HashMap::new();
+    let mut printings: Vec<ScryfallPrinting> = vec![];
+
+    serde_json::Deserializer::from_reader(BufReader::new(file))
+        .deserialize_seq(ForEachCard(|card: Value| {
+            let printing = ScryfallPrinting::deserialize(&card).expect("unable to parse printing");
+            if printing.oracle_id.is_some() {
+                printings.push(printing);
+            }
+
+            let card = ScryfallCard::deserialize(&card).expect("unable to parse card");
+            if card.games().contains(&String::from("arena"))
+                && !UNWANTED_LAYOUTS.contains(&card.layout().as_str())
+                && card.lang() == "en"
+            {
+                add_printing(&mut cards, card);
+            }
+        }))
+        .expect("unable to parse JSON");
+
+    // Get a list of all the alchemy card names, with the 'A-' prefix stripped, and remove cards with that name
+    let alchemy_card_names: Vec<String> = cards
+        .values()
+        .filter(|card| card.is_rebalanced())
+        .map(|card| strip_alchemy_prefix(&card.name()))
+        .collect();
+    cards.retain(|_, card| !alchemy_card_names.contains(&card.name().to_string()));
+
+    println!(
+        "Read {} cards and {} printings from {path}",
+        cards.len(),
+        printings.len()
+    );
+    DefaultCards { cards, printings }
+}
+
+// Keeps the newest printing of a card, with the lowest rarity it was printed at across all of them
+fn add_printing(cards: &mut HashMap<String, ScryfallCard>, mut card: ScryfallCard) {
+    card.set_lowest_rarity(card.rarity());
+    let new_released_at = card.released_at();
+
+    cards
+        .entry(card.oracle_id())
+        .and_modify(|existing_card| {
+            if existing_card.released_at() < new_released_at {
+                *existing_card = card.clone();
+            }
+
+            // Update lowest_rarity if it's empty or the new card has a lower rarity
+            if is_lower_rarity(&existing_card.lowest_rarity(), &card.rarity()) {
+                existing_card.set_lowest_rarity(card.rarity());
+            }
+        })
+        .or_insert(card);
+}
+
+// rarity order: common < uncommon < rare < mythic
+fn is_lower_rarity(current: &str, new: &str) -> bool {
+    match (current, new) {
+        ("common", _) => false,
+        ("uncommon", "common") => true,
+        ("uncommon", _) => false,
+        ("rare", "common") | ("rare", "uncommon") => true,
+        ("rare", _) => false,
+        ("mythic", "rare") | ("mythic", "uncommon") | ("mythic", "common") => true,
+        _ => false,
+    }
+}
+
+// Hands every element of the top level array to the closure as soon as it's parsed
+struct ForEachCard<F>(F);
+
+impl<'de, F: FnMut(Value)> Visitor<'de> for ForEachCard<F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of Scryfall cards")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(card) = seq.next_element::<Value>()? {
+            (self.0)(card);
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 #!/usr/bin/env bash
 
-sudo -i -u postgres -H -- psql -d brawlhub -h localhost -c "CREATE TABLE IF NOT EXISTS card (
+sudo -i -u postgres -H -- psql -d brawlhub -h localhost -c "CREATE EXTENSION IF NOT EXISTS unaccent;
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE OR REPLACE FUNCTION is_land(type_line text) RETURNS bool LANGUAGE sql IMMUTABLE AS 'SELECT type_line LIKE ''%Land%''';
+CREATE TABLE IF NOT EXISTS card (
     oracle_id uuid NOT NULL PRIMARY KEY,
     name_full text NOT NULL,
     name_front text NOT NULL,
@@ -24,6 +27,7 @@ sudo -i -u postgres -H -- psql -d brawlhub -h localhost -c "CREATE TABLE IF NOT
     colors_back char(1)[],
     color_identity char(1)[] NOT NULL,
     is_legal bool NOT NULL,
+    is_legal_standard bool NOT NULL DEFAULT false,
     is_legal_commander bool NOT NULL,
     is_rebalanced bool NOT NULL,
     image_small text NOT NULL,
@@ -35,7 +39,8 @@ sudo -i -u postgres -H -- psql -d brawlhub -h localhost -c "CREATE TABLE IF NOT
     image_normal_back text,
     image_large_back text,
     image_art_crop_back text,
-    image_border_crop_back text
+    image_border_crop_back text,
+    arena_id int
 );
 CREATE TABLE IF NOT EXISTS deck (
     id SERIAL PRIMARY KEY,
@@ -46,8 +51,16 @@ CREATE TABLE IF NOT EXISTS deck (
     date_updated bigint NOT NULL,
     commander uuid REFERENCES card(oracle_id) NOT NULL,
     companion uuid REFERENCES card(oracle_id),
-    color_identity char(1)[] NOT NULL
+    color_identity char(1)[] NOT NULL,
+    format text NOT NULL DEFAULT 'historic',
+    archetype int
 );
+ALTER TABLE card ADD COLUMN IF NOT EXISTS is_legal_standard bool NOT NULL DEFAULT false;
+ALTER TABLE deck ADD COLUMN IF NOT EXISTS format text NOT NULL DEFAULT 'historic';
+ALTER TABLE deck ADD COLUMN IF NOT EXISTS archetype int;
+ALTER TABLE card ADD COLUMN IF NOT EXISTS arena_id int;
+ALTER TABLE deck ADD COLUMN IF NOT EXISTS mox_deck_id text UNIQUE;
+ALTER TABLE deck ADD COLUMN IF NOT EXISTS source text;
 CREATE TABLE IF NOT EXISTS decklist (
     oracle_id uuid REFERENCES card(oracle_id),
     deck_id int REFERENCES deck(id),
@@ -55,4 +68,63 @@ CREATE TABLE IF NOT EXISTS decklist (
     is_commander bool NOT NULL DEFAULT false,
     quantity integer NOT NULL,
     PRIMARY KEY (oracle_id, deck_id)
+);
+CREATE TABLE IF NOT EXISTS commander_similarity (
+    commander uuid REFERENCES card(oracle_id) NOT NULL,
+    similar_commander uuid REFERENCES card(oracle_id) NOT NULL,
+    similarity double precision NOT NULL,
+    shared_cards int NOT NULL,
+    PRIMARY KEY (commander, similar_commander)
+);
+CREATE TABLE IF NOT EXISTS commander_archetype (
+    commander uuid REFERENCES card(oracle_id) NOT NULL,
+    archetype int NOT NULL,
+    label text NOT NULL,
+    decks int NOT NULL,
+    PRIMARY KEY (commander, archetype)
+);
+CREATE TABLE IF NOT EXISTS scryfall_id (
+    scryfall_id uuid PRIMARY KEY,
+    oracle_id uuid REFERENCES card(oracle_id)
+);
+CREATE TABLE IF NOT EXISTS printing (
+    scryfall_id uuid PRIMARY KEY,
+    oracle_id uuid REFERENCES card(oracle_id) NOT NULL,
+    arena_id int,
+    set_code text NOT NULL,
+    set_name text NOT NULL,
+    collector_number text NOT NULL,
+    released_at date NOT NULL,
+    rarity text NOT NULL,
+    lang text NOT NULL
+);
+CREATE INDEX IF NOT EXISTS printing_oracle_id ON printing (oracle_id);
+CREATE INDEX IF NOT EXISTS printing_arena_id ON printing (arena_id);
+DROP INDEX IF EXISTS scryfall_id_arena_id;
+ALTER TABLE scryfall_id DROP COLUMN IF EXISTS arena_id;
+CREATE TABLE IF NOT EXISTS collection (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    date_created bigint NOT NULL
+);
+CREATE TABLE IF NOT EXISTS collection_card (
+    collection_id uuid REFERENCES collection(id) ON DELETE CASCADE NOT NULL,
+    oracle_id uuid REFERENCES card(oracle_id) NOT NULL,
+    quantity int NOT NULL,
+    PRIMARY KEY (collection_id, oracle_id)
+);
+CREATE TABLE IF NOT EXISTS top_cards (
+    oracle_id uuid PRIMARY KEY,
+    name_full text NOT NULL,
+    color_identity char(1)[] NOT NULL,
+    total_decks_could_play integer NOT NULL,
+    total_decks_with_card integer NOT NULL,
+    rank real NOT NULL
+);
+CREATE TABLE IF NOT EXISTS top_cards_staging (LIKE top_cards INCLUDING ALL);
+CREATE TABLE IF NOT EXISTS sync_state (
+    source text NOT NULL,
+    format text NOT NULL,
+    cursor bigint NOT NULL,
+    date_updated bigint NOT NULL,
+    PRIMARY KEY (source, format)
 )"
\ No newline at end of file
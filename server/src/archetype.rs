@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+// Query parameter limiting a commander's decks to one archetype, eg. `?archetype=2`
+// Archetypes are found by the migration tool and numbered from 1, the most played one first
+#[derive(Debug, Deserialize)]
+pub struct ArchetypeParams {
+    pub archetype: Option<i32>,
+}
+
+impl ArchetypeParams {
+    pub fn archetype(&self) -> Result<Option<i32>, ApiError> {
+        match self.archetype {
+            Some(archetype) if archetype < 1 => Err(ApiError::bad_request(format!(
+                "invalid archetype '{archetype}', archetypes are numbered from 1"
+            ))),
+            archetype => Ok(archetype),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// A card played in a commander's decks, `decks` is how many of them play it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AverageDeckCard {
+    pub oracle_id: String,
+    pub name_full: String,
+    pub slug: String,
+    pub layout: String,
+    pub type_line_front: String,
+    pub mana_cost_front: Option<String>,
+    pub cmc: f32,
+    pub color_identity: Vec<String>,
+    pub lowest_rarity: String,
+    pub image_small: String,
+    pub image_normal: String,
+    pub decks: i64,
+    pub quantity: i32,
+}
+
+impl AverageDeckCard {
+    fn is_land(&self) -> bool {
+        self.type_line_front.contains("Land")
+    }
+}
+
+// Builds the deck from the most played cards, sorted by how many decks play them
+// `basics` carry the total number of copies played across every deck in `quantity`
+pub fn assemble(
+    candidates: Vec<AverageDeckCard>,
+    basics: Vec<AverageDeckCard>,
+    deck_size: i32,
+    land_count: i32,
+    nonbasic_land_count: i32,
+) -> Vec<AverageDeckCard> {
+    // The commander takes one of the slots
+    let slots = deck_size - 1;
+    let land_count = land_count.min(slots);
+    let nonland_count = slots - land_count;
+
+    let (lands, nonlands): (Vec<AverageDeckCard>, Vec<AverageDeckCard>) =
+        candidates.into_iter().partition(AverageDeckCard::is_land);
+
+    let mut nonlands = nonlands.into_iter();
+    let mut lands = lands.into_iter();
+    let mut deck: Vec<AverageDeckCard> = nonlands
+        .by_ref()
+        .take(nonland_count as usize)
+        .map(|card| AverageDeckCard {
+            quantity: 1,
+            ..card
+        })
+        .collect();
+    let nonbasic_lands: Vec<AverageDeckCard> = lands
+        .by_ref()
+        .take(nonbasic_land_count.min(land_count) as usize)
+        .map(|card| AverageDeckCard {
+            quantity: 1,
+            ..card
+        })
+        .collect();
+    let basic_count = land_count - nonbasic_lands.len() as i32;
+    deck.extend(nonbasic_lands);
+
+    // Not enough cards have been played with this commander, so top up with whatever is left
+    let missing = slots - basic_count - deck.len() as i32;
+    deck.extend(
+        nonlands
+            .chain(lands)
+            .take(missing.max(0) as usize)
+            .map(|card| AverageDeckCard {
+                quantity: 1,
+                ..card
+            }),
+    );
+
+    // Basics fill every slot that's still open
+    let basic_count = slots - deck.len() as i32;
+    deck.extend(split_basics(basics, basic_count));
+
+    deck.sort_by(|a, b| {
+        a.is_land()
+            .cmp(&b.is_land())
+            .then(a.cmc.total_cmp(&b.cmc))
+            .then(a.name_full.cmp(&b.name_full))
+    });
+    deck
+}
+
+// Splits `count` basics in the same proportions they're played in, using the largest remainder
+fn split_basics(basics: Vec<AverageDeckCard>, count: i32) -> Vec<AverageDeckCard> {
+    let total: i32 = basics.iter().map(|basic| basic.quantity).sum();
+    if total == 0 || count <= 0 {
+        return vec![];
+    }
+
+    let shares: Vec<f64> = basics
+        .iter()
+        .map(|basic| basic.quantity as f64 * count as f64 / total as f64)
+        .collect();
+    let mut quantities: Vec<i32> = shares.iter().map(|share| share.floor() as i32).collect();
+
+    let mut by_remainder: Vec<usize> = (0..basics.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor()))
+    });
+    let left = count - quantities.iter().sum::<i32>();
+    for &i in by_remainder.iter().take(left as usize) {
+        quantities[i] += 1;
+    }
+
+    basics
+        .into_iter()
+        .zip(quantities)
+        .filter(|(_, quantity)| *quantity > 0)
+        .map(|(basic, quantity)| AverageDeckCard { quantity, ..basic })
+        .collect()
+}
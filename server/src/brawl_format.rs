@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+// Query parameter limiting statistics to decks of one Brawl format, eg. `?format=standard`
+#[derive(Debug, Deserialize)]
+pub struct FormatParams {
+    pub format: Option<String>,
+}
+
+impl FormatParams {
+    // Value stored in deck.format, None means decks of every format
+    pub fn format(&self) -> Result<Option<&'static str>, ApiError> {
+        match self.format.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None => Ok(None),
+            Some("standard") => Ok(Some("standard")),
+            Some("historic") => Ok(Some("historic")),
+            Some(format) => Err(ApiError::bad_request(format!(
+                "invalid format '{format}', expected 'standard' or 'historic'"
+            ))),
+        }
+    }
+
+    // Average decks can't mix 60 and 100 card decks, so without a format they use the deck.format default
+    pub fn format_or_default(&self) -> Result<&'static str, ApiError> {
+        Ok(self.format()?.unwrap_or("historic"))
+    }
+}
@@ -0,0 +1,477 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::error::ApiError;
+
+// A parsed Scryfall-like query, eg. `t:creature o:"sacrifice a" id<=br cmc<=3 -is:rebalanced`
+// Every term has to match, words without a key are matched against the card name
+#[derive(Debug)]
+pub struct CardQuery {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug)]
+enum Filter {
+    Name(String),
+    OracleText(String),
+    TypeLine(String),
+    Colors(Comparison, Vec<String>),
+    ColorIdentity(Comparison, Vec<String>),
+    Cmc(Comparison, f32),
+    Rarity(Comparison, &'static str),
+    IsCommander,
+    IsRebalanced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+const RARITIES: [&str; 4] = ["common", "uncommon", "rare", "mythic"];
+
+impl Comparison {
+    fn sql(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "<>",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+impl CardQuery {
+    pub fn parse(query: &str) -> Result<Self, ApiError> {
+        let terms = tokenize(query)?
+            .into_iter()
+            .map(|token| parse_term(&token))
+            .collect::<Result<Vec<Term>, ApiError>>()?;
+
+        if terms.is_empty() {
+            return Err(ApiError::bad_request("search query is empty"));
+        }
+
+        Ok(CardQuery { terms })
+    }
+
+    // Pushes ` AND <term>` for every term, all values are bound as parameters
+    // A negated term is NOT COALESCE(..., false) so cards with a NULL column, eg. no back face, still match it
+    pub fn push_filters(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        for term in &self.terms {
+            builder.push(" AND ");
+            if term.negated {
+                builder.push("NOT COALESCE((");
+                term.filter.push_sql(builder);
+                builder.push("), false)");
+            } else {
+                builder.push("(");
+                term.filter.push_sql(builder);
+                builder.push(")");
+            }
+        }
+    }
+}
+
+impl Filter {
+    fn push_sql(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        match self {
+            Filter::Name(name) => {
+                builder
+                    .push("unaccent(card.name_full) ILIKE unaccent(")
+                    .push_bind(like_pattern(name))
+                    .push(")");
+            }
+            Filter::OracleText(text) => {
+                builder
+                    .push("card.oracle_text ILIKE ")
+                    .push_bind(like_pattern(text))
+                    .push(" OR card.oracle_text_back ILIKE ")
+                    .push_bind(like_pattern(text));
+            }
+            Filter::TypeLine(type_line) => {
+                builder
+                    .push("card.type_line_full ILIKE ")
+                    .push_bind(like_pattern(type_line));
+            }
+            Filter::Colors(comparison, colors) => {
+                push_color_comparison(builder, "COALESCE(card.colors, '{}')", *comparison, colors)
+            }
+            Filter::ColorIdentity(comparison, colors) => {
+                push_color_comparison(builder, "card.color_identity", *comparison, colors)
+            }
+            Filter::Cmc(comparison, cmc) => {
+                builder
+                    .push(format!("card.cmc {} ", comparison.sql()))
+                    .push_bind(*cmc);
+            }
+            Filter::Rarity(comparison, rarity) => {
+                builder
+                    .push(format!(
+                        "array_position(ARRAY['common','uncommon','rare','mythic'], card.rarity) {} ",
+                        comparison.sql()
+                    ))
+                    .push("array_position(ARRAY['common','uncommon','rare','mythic'], ")
+                    .push_bind(*rarity)
+                    .push(")");
+            }
+            Filter::IsCommander => {
+                builder.push("card.is_legal_commander");
+            }
+            Filter::IsRebalanced => {
+                builder.push("card.is_rebalanced");
+            }
+        }
+    }
+}
+
+// Matches the value anywhere in the column, wildcards typed by the user are escaped like in get_card
+fn like_pattern(value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{value}%")
+}
+
+// Color sets compare like Scryfall, `<=` is "within", `>=` is "at least" and `=` is "exactly"
+fn push_color_comparison(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    column: &str,
+    comparison: Comparison,
+    colors: &[String],
+) {
+    let contains = |builder: &mut QueryBuilder<'_, Postgres>| {
+        builder
+            .push(format!("{column} @> "))
+            .push_bind(colors.to_vec())
+            .push("::char(1)[]");
+    };
+    let within = |builder: &mut QueryBuilder<'_, Postgres>| {
+        builder
+            .push(format!("{column} <@ "))
+            .push_bind(colors.to_vec())
+            .push("::char(1)[]");
+    };
+
+    match comparison {
+        Comparison::Equal => {
+            contains(builder);
+            builder.push(" AND ");
+            within(builder);
+        }
+        Comparison::NotEqual => {
+            builder.push("NOT (");
+            contains(builder);
+            builder.push(" AND ");
+            within(builder);
+            builder.push(")");
+        }
+        Comparison::LessOrEqual => within(builder),
+        Comparison::GreaterOrEqual => contains(builder),
+        Comparison::Less => {
+            within(builder);
+            builder.push(" AND NOT ");
+            contains(builder);
+        }
+        Comparison::Greater => {
+            contains(builder);
+            builder.push(" AND NOT ");
+            within(builder);
+        }
+    }
+}
+
+// Splits on whitespace, keeping double quoted values like o:"draw a card" together
+fn tokenize(query: &str) -> Result<Vec<String>, ApiError> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut in_quotes = false;
+
+    for char in query.chars() {
+        match char {
+            '"' => in_quotes = !in_quotes,
+            char if char.is_whitespace() && !in_quotes => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            char => token.push(char),
+        }
+    }
+
+    if in_quotes {
+        return Err(ApiError::bad_request("unclosed quote in search query"));
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<Term, ApiError> {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(token) if !token.is_empty() => (true, token),
+        _ => (false, token),
+    };
+
+    let Some((key, comparison, value)) = split_term(token) else {
+        return Ok(Term {
+            negated,
+            filter: Filter::Name(token.to_string()),
+        });
+    };
+
+    let key = key.to_ascii_lowercase();
+    let filter = match key.as_str() {
+        "o" | "oracle" => Filter::OracleText(expect_colon(&key, comparison, value)?),
+        "t" | "type" => Filter::TypeLine(expect_colon(&key, comparison, value)?),
+        // Scryfall reads `c:` as "at least these colors" and `id:` as "within this identity"
+        "c" | "color" => {
+            let colors = parse_colors(value)?;
+            Filter::Colors(
+                colon_as_for_colors(comparison, Comparison::GreaterOrEqual, &colors),
+                colors,
+            )
+        }
+        "id" | "identity" | "ci" => {
+            let colors = parse_colors(value)?;
+            Filter::ColorIdentity(
+                colon_as_for_colors(comparison, Comparison::LessOrEqual, &colors),
+                colors,
+            )
+        }
+        "cmc" | "mv" => Filter::Cmc(
+            colon_as(comparison, Comparison::Equal),
+            value.parse().map_err(|_| {
+                ApiError::bad_request(format!("invalid {key} '{value}', expected a number"))
+            })?,
+        ),
+        "r" | "rarity" => Filter::Rarity(
+            colon_as(comparison, Comparison::Equal),
+            parse_rarity(value)?,
+        ),
+        "is" => match (comparison, value.to_ascii_lowercase().as_str()) {
+            (None, "commander") => Filter::IsCommander,
+            (None, "rebalanced") => Filter::IsRebalanced,
+            _ => {
+                return Err(ApiError::bad_request(format!(
+                    "invalid is:{value}, expected is:commander or is:rebalanced"
+                )))
+            }
+        },
+        _ => {
+            return Err(ApiError::bad_request(format!(
+                "unknown search key '{key}', expected one of: o, t, c, id, cmc, r, is"
+            )))
+        }
+    };
+
+    Ok(Term { negated, filter })
+}
+
+// "cmc>=3" -> ("cmc", Some(GreaterOrEqual), "3"), a `:` comes back as None
+fn split_term(token: &str) -> Option<(&str, Option<Comparison>, &str)> {
+    let start = token.find([':', '=', '<', '>', '!'])?;
+    let (key, rest) = token.split_at(start);
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let (comparison, length) = if rest.starts_with("<=") {
+        (Some(Comparison::LessOrEqual), 2)
+    } else if rest.starts_with(">=") {
+        (Some(Comparison::GreaterOrEqual), 2)
+    } else if rest.starts_with("!=") {
+        (Some(Comparison::NotEqual), 2)
+    } else if rest.starts_with('<') {
+        (Some(Comparison::Less), 1)
+    } else if rest.starts_with('>') {
+        (Some(Comparison::Greater), 1)
+    } else if rest.starts_with('=') {
+        (Some(Comparison::Equal), 1)
+    } else if rest.starts_with(':') {
+        (None, 1)
+    } else {
+        return None;
+    };
+
+    Some((key, comparison, &rest[length..]))
+}
+
+fn colon_as(comparison: Option<Comparison>, default: Comparison) -> Comparison {
+    comparison.unwrap_or(default)
+}
+
+// `c:c` and `id:c` mean colorless, "at least no colors" would match every card
+fn colon_as_for_colors(
+    comparison: Option<Comparison>,
+    default: Comparison,
+    colors: &[String],
+) -> Comparison {
+    if colors.is_empty() {
+        colon_as(comparison, Comparison::Equal)
+    } else {
+        colon_as(comparison, default)
+    }
+}
+
+fn expect_colon(
+    key: &str,
+    comparison: Option<Comparison>,
+    value: &str,
+) -> Result<String, ApiError> {
+    match comparison {
+        None if !value.is_empty() => Ok(value.to_string()),
+        None => Err(ApiError::bad_request(format!("{key}: needs a value"))),
+        Some(_) => Err(ApiError::bad_request(format!(
+            "{key} only supports ':', eg. {key}:value"
+        ))),
+    }
+}
+
+fn parse_colors(value: &str) -> Result<Vec<String>, ApiError> {
+    let value = value.to_ascii_lowercase();
+    let letters = match value.as_str() {
+        "colorless" | "c" => "",
+        "white" => "w",
+        "blue" => "u",
+        "black" => "b",
+        "red" => "r",
+        "green" => "g",
+        letters => letters,
+    };
+
+    let mut colors: Vec<String> = vec![];
+    for letter in letters.chars() {
+        if !"wubrg".contains(letter) {
+            return Err(ApiError::bad_request(format!(
+                "invalid color '{value}', expected a combination of W, U, B, R and G"
+            )));
+        }
+        let color = letter.to_ascii_uppercase().to_string();
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+    Ok(colors)
+}
+
+fn parse_rarity(value: &str) -> Result<&'static str, ApiError> {
+    let value = value.to_ascii_lowercase();
+    RARITIES
+        .into_iter()
+        .find(|rarity| *rarity == value || rarity[..1] == value)
+        .ok_or_else(|| {
+            ApiError::bad_request(format!(
+                "invalid rarity '{value}', expected one of: {}",
+                RARITIES.join(", ")
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn where_clause(query: &str) -> String {
+        let mut builder = QueryBuilder::new("WHERE true");
+        CardQuery::parse(query).unwrap().push_filters(&mut builder);
+        builder.sql().to_string()
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_values_together() {
+        assert_eq!(
+            tokenize(r#"t:creature  o:"draw a card" -is:rebalanced"#).unwrap(),
+            vec!["t:creature", "o:draw a card", "-is:rebalanced"]
+        );
+    }
+
+    #[test]
+    fn tokenize_rejects_unclosed_quote() {
+        assert!(tokenize(r#"o:"draw a card"#).is_err());
+    }
+
+    #[test]
+    fn split_term_reads_comparisons() {
+        assert_eq!(
+            split_term("cmc>=3"),
+            Some(("cmc", Some(Comparison::GreaterOrEqual), "3"))
+        );
+        assert_eq!(
+            split_term("id<br"),
+            Some(("id", Some(Comparison::Less), "br"))
+        );
+        assert_eq!(
+            split_term("r!=c"),
+            Some(("r", Some(Comparison::NotEqual), "c"))
+        );
+        assert_eq!(split_term("t:goblin"), Some(("t", None, "goblin")));
+        assert_eq!(split_term("krenko"), None);
+        assert_eq!(split_term(":goblin"), None);
+    }
+
+    #[test]
+    fn parse_colors_reads_names_and_letters() {
+        assert_eq!(parse_colors("c").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_colors("colorless").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_colors("blue").unwrap(), vec!["U"]);
+        assert_eq!(
+            parse_colors("wubrgw").unwrap(),
+            vec!["W", "U", "B", "R", "G"]
+        );
+        assert!(parse_colors("wx").is_err());
+    }
+
+    #[test]
+    fn parse_rarity_reads_names_and_initials() {
+        assert_eq!(parse_rarity("M").unwrap(), "mythic");
+        assert_eq!(parse_rarity("uncommon").unwrap(), "uncommon");
+        assert!(parse_rarity("special").is_err());
+    }
+
+    #[test]
+    fn negated_terms_match_null_columns() {
+        let term = parse_term("-o:flying").unwrap();
+        assert!(term.negated);
+        assert!(matches!(term.filter, Filter::OracleText(ref text) if text == "flying"));
+        assert_eq!(
+            where_clause("-o:flying"),
+            "WHERE true AND NOT COALESCE((card.oracle_text ILIKE $1 OR card.oracle_text_back ILIKE $2), false)"
+        );
+        assert!(!parse_term("-").unwrap().negated);
+    }
+
+    #[test]
+    fn colorless_compares_exactly() {
+        for query in ["c:c", "id:colorless"] {
+            let term = parse_term(query).unwrap();
+            assert!(matches!(
+                term.filter,
+                Filter::Colors(Comparison::Equal, _) | Filter::ColorIdentity(Comparison::Equal, _)
+            ));
+        }
+        assert!(matches!(
+            parse_term("c:r").unwrap().filter,
+            Filter::Colors(Comparison::GreaterOrEqual, _)
+        ));
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern(r"50%_off\"), r"%50\%\_off\\%");
+    }
+}
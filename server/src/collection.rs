@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+const ARENA_ID_KEYS: [&str; 5] = ["arena_id", "arenaid", "id", "grpid", "grp_id"];
+const COUNT_KEYS: [&str; 4] = ["count", "quantity", "qty", "owned"];
+
+// Query parameter marking the cards of an imported collection, eg. `?collection=<id from POST /collection>`
+#[derive(Debug, Deserialize)]
+pub struct CollectionParams {
+    pub collection: Option<Uuid>,
+}
+
+// Parses a collection exported by an Arena tracker into (arena_id, count), with duplicate arena ids added up
+// JSON is either a list of objects, eg. `[{"arena_id": 70001, "count": 4}]`, or counts by arena id, eg. `{"70001": 4}`
+// CSV needs an arena id and a count column, without a header the first two columns are used
+pub fn parse_collection(text: &str) -> Result<Vec<(i32, i32)>, ApiError> {
+    let text = text.trim();
+    let cards = if text.starts_with('[') || text.starts_with('{') {
+        parse_json(text)?
+    } else {
+        parse_csv(text)?
+    };
+
+    let mut counts: HashMap<i32, i32> = HashMap::new();
+    for (arena_id, count) in cards {
+        if count > 0 {
+            *counts.entry(arena_id).or_default() += count;
+        }
+    }
+    if counts.is_empty() {
+        return Err(ApiError::bad_request("no cards found in the collection"));
+    }
+
+    let mut counts: Vec<(i32, i32)> = counts.into_iter().collect();
+    counts.sort();
+    Ok(counts)
+}
+
+fn parse_json(text: &str) -> Result<Vec<(i32, i32)>, ApiError> {
+    let json: Value = serde_json::from_str(text)
+        .map_err(|e| ApiError::bad_request(format!("invalid JSON collection: {e}")))?;
+
+    match json {
+        Value::Object(counts) => counts
+            .iter()
+            .map(|(arena_id, count)| {
+                Ok((
+                    parse_number(&Value::String(arena_id.clone()), "arena id")?,
+                    parse_number(count, "count")?,
+                ))
+            })
+            .collect(),
+        Value::Array(cards) => cards
+            .iter()
+            .map(|card| {
+                let field = |keys: &[&str], name: &str| -> Result<i32, ApiError> {
+                    let value = card
+                        .as_object()
+                        .and_then(|card| {
+                            card.iter()
+                                .find(|(key, _)| keys.contains(&key.to_ascii_lowercase().as_str()))
+                        })
+                        .map(|(_, value)| value)
+                        .ok_or_else(|| {
+                            ApiError::bad_request(format!("collection entry {card} has no {name}"))
+                        })?;
+                    parse_number(value, name)
+                };
+                Ok((
+                    field(&ARENA_ID_KEYS, "arena id")?,
+                    field(&COUNT_KEYS, "count")?,
+                ))
+            })
+            .collect(),
+        _ => Err(ApiError::bad_request(
+            "JSON collection must be a list of cards or an object of counts by arena id",
+        )),
+    }
+}
+
+fn parse_number(value: &Value, name: &str) -> Result<i32, ApiError> {
+    let number = match value {
+        Value::Number(number) => number.as_i64(),
+        Value::String(string) => string.trim().parse().ok(),
+        _ => None,
+    };
+    number
+        .and_then(|number| i32::try_from(number).ok())
+        .ok_or_else(|| ApiError::bad_request(format!("invalid {name} {value}")))
+}
+
+fn parse_csv(text: &str) -> Result<Vec<(i32, i32)>, ApiError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+
+    let header = lines.peek().map(|(_, line)| split_csv_line(line));
+    let (arena_id_column, count_column) = match header {
+        Some(header)
+            if header
+                .first()
+                .is_some_and(|field| field.parse::<i32>().is_err()) =>
+        {
+            lines.next();
+            let column = |keys: &[&str], name: &str| {
+                header
+                    .iter()
+                    .position(|field| keys.contains(&field.to_ascii_lowercase().as_str()))
+                    .ok_or_else(|| {
+                        ApiError::bad_request(format!(
+                            "CSV header needs a {name} column, one of: {}",
+                            keys.join(", ")
+                        ))
+                    })
+            };
+            (
+                column(&ARENA_ID_KEYS, "arena id")?,
+                column(&COUNT_KEYS, "count")?,
+            )
+        }
+        _ => (0, 1),
+    };
+
+    lines
+        .map(|(i, line)| {
+            let fields = split_csv_line(line);
+            let field = |column: usize, name: &str| -> Result<i32, ApiError> {
+                fields
+                    .get(column)
+                    .and_then(|field| field.parse().ok())
+                    .ok_or_else(|| {
+                        ApiError::bad_request(format!("invalid {name} on line {}: {line}", i + 1))
+                    })
+            };
+            Ok((
+                field(arena_id_column, "arena id")?,
+                field(count_column, "count")?,
+            ))
+        })
+        .collect()
+}
+
+// Fields can be quoted, eg. `70001,"Krenko, Mob Boss",1`
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' | ';' | '\t' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            char => field.push(char),
+        }
+    }
+    fields.push(field);
+    fields
+        .iter()
+        .map(|field| field.trim().to_string())
+        .collect()
+}
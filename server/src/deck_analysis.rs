@@ -0,0 +1,143 @@
+// Mana values from 0 to 6, everything costing 7 or more shares the last bucket
+const CURVE_BUCKETS: usize = 8;
+
+// The parts of a card in the deck that the analysis looks at
+#[derive(Debug, Clone)]
+pub struct AnalyzedCard {
+    pub cmc: f32,
+    pub type_line_front: String,
+    pub mana_cost_front: Option<String>,
+    pub mana_cost_back: Option<String>,
+    pub oracle_text: Option<String>,
+    pub quantity: i32,
+}
+
+// Colored mana symbols in the deck's mana costs, hybrid symbols count towards both colors
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct Pips {
+    pub w: f64,
+    pub u: f64,
+    pub b: f64,
+    pub r: f64,
+    pub g: f64,
+    pub c: f64,
+}
+
+// Counts are f64 so the average of a commander's decks has the same shape as a single deck
+// `curve` only counts nonland cards, `curve[2]` is how many cost 2
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct DeckAnalysis {
+    pub curve: [f64; CURVE_BUCKETS],
+    pub average_cmc: f64,
+    pub pips: Pips,
+    pub lands: f64,
+    pub nonlands: f64,
+    pub land_ratio: f64,
+    pub ramp: f64,
+}
+
+impl Pips {
+    fn add(&mut self, mana_cost: &str, quantity: f64) {
+        for symbol in mana_cost.split('}') {
+            let symbol = symbol.trim_start_matches('{');
+            for part in symbol.split('/') {
+                match part {
+                    "W" => self.w += quantity,
+                    "U" => self.u += quantity,
+                    "B" => self.b += quantity,
+                    "R" => self.r += quantity,
+                    "G" => self.g += quantity,
+                    "C" => self.c += quantity,
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        for pips in [
+            &mut self.w,
+            &mut self.u,
+            &mut self.b,
+            &mut self.r,
+            &mut self.g,
+            &mut self.c,
+        ] {
+            *pips *= factor;
+        }
+    }
+}
+
+// Same rule as the is_land SQL function used by the average deck and profile queries
+fn is_land(card: &AnalyzedCard) -> bool {
+    card.type_line_front.contains("Land")
+}
+
+// Nonland cards that make mana or put extra lands onto the battlefield, eg. Llanowar Elves or Cultivate
+fn is_ramp(card: &AnalyzedCard) -> bool {
+    let oracle_text = card
+        .oracle_text
+        .as_deref()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    oracle_text.contains("add {")
+        || oracle_text.contains("add one mana")
+        || oracle_text.contains("add two mana")
+        || oracle_text.contains("add three mana")
+        || oracle_text.contains("play an additional land")
+        || (oracle_text.contains("search your library for")
+            && oracle_text.contains("land card")
+            && oracle_text.contains("onto the battlefield"))
+}
+
+pub fn analyze(cards: &[AnalyzedCard]) -> DeckAnalysis {
+    let mut analysis = DeckAnalysis::default();
+    let mut total_cmc = 0.0;
+
+    for card in cards {
+        let quantity = card.quantity as f64;
+        for mana_cost in [&card.mana_cost_front, &card.mana_cost_back]
+            .into_iter()
+            .flatten()
+        {
+            analysis.pips.add(mana_cost, quantity);
+        }
+
+        if is_land(card) {
+            analysis.lands += quantity;
+            continue;
+        }
+        analysis.nonlands += quantity;
+        analysis.curve[(card.cmc as usize).min(CURVE_BUCKETS - 1)] += quantity;
+        total_cmc += card.cmc as f64 * quantity;
+        if is_ramp(card) {
+            analysis.ramp += quantity;
+        }
+    }
+
+    if analysis.nonlands > 0.0 {
+        analysis.average_cmc = total_cmc / analysis.nonlands;
+    }
+    if analysis.lands + analysis.nonlands > 0.0 {
+        analysis.land_ratio = analysis.lands / (analysis.lands + analysis.nonlands);
+    }
+    analysis
+}
+
+// Turns the analysis of every card played across `decks` decks into the numbers of an average deck
+// The ratios aren't scaled, so the average cmc is over all the nonland cards played rather than a mean of every deck's
+pub fn per_deck(mut total: DeckAnalysis, decks: i64) -> DeckAnalysis {
+    if decks == 0 {
+        return DeckAnalysis::default();
+    }
+
+    let factor = 1.0 / decks as f64;
+    for bucket in &mut total.curve {
+        *bucket *= factor;
+    }
+    total.pips.scale(factor);
+    total.lands *= factor;
+    total.nonlands *= factor;
+    total.ramp *= factor;
+    total
+}
@@ -0,0 +1,130 @@
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+// Query parameter picking the export format, eg. `?format=moxfield`
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Arena,
+    Moxfield,
+    Csv,
+    Json,
+}
+
+impl ExportParams {
+    pub fn format(&self) -> Result<ExportFormat, ApiError> {
+        match self
+            .format
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("arena") => Ok(ExportFormat::Arena),
+            Some("moxfield") => Ok(ExportFormat::Moxfield),
+            Some("csv") => Ok(ExportFormat::Csv),
+            Some("json") => Ok(ExportFormat::Json),
+            Some(format) => Err(ApiError::bad_request(format!(
+                "invalid format '{format}', expected one of: arena, moxfield, csv, json"
+            ))),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Arena | ExportFormat::Moxfield => "text/plain; charset=utf-8",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ExportCard {
+    pub oracle_id: String,
+    pub name_full: String,
+    pub name_front: String,
+    pub layout: String,
+    pub quantity: i32,
+    pub is_commander: bool,
+    pub is_companion: bool,
+    pub set_code: Option<String>,
+    pub collector_number: Option<String>,
+}
+
+impl ExportCard {
+    fn section(&self) -> &'static str {
+        if self.is_commander {
+            "Commander"
+        } else if self.is_companion {
+            "Companion"
+        } else {
+            "Deck"
+        }
+    }
+
+    // Arena only knows double faced cards by their front face, and separates split card halves with "///"
+    // The printing is added when it's known, eg. "Shock (M21) 159"
+    fn arena_name(&self) -> String {
+        let name = match self.layout.as_str() {
+            "transform" | "modal_dfc" | "adventure" => self.name_front.clone(),
+            _ => self.name_full.replace(" // ", " /// "),
+        };
+        match (&self.set_code, &self.collector_number) {
+            (Some(set_code), Some(collector_number)) => format!(
+                "{name} ({}) {collector_number}",
+                set_code.to_ascii_uppercase()
+            ),
+            _ => name,
+        }
+    }
+}
+
+pub fn render(cards: &[ExportCard], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Arena => render_sections(cards, ExportCard::arena_name),
+        ExportFormat::Moxfield => render_sections(cards, |card| card.name_full.clone()),
+        ExportFormat::Csv => render_csv(cards),
+        ExportFormat::Json => serde_json::to_string(cards).expect("serialize exported deck"),
+    }
+}
+
+// Commander
+// 1 Krenko, Mob Boss
+//
+// Deck
+// 1 Goblin Bombardment
+fn render_sections(cards: &[ExportCard], name: impl Fn(&ExportCard) -> String) -> String {
+    ["Commander", "Companion", "Deck"]
+        .into_iter()
+        .filter_map(|section| {
+            let lines: Vec<String> = cards
+                .iter()
+                .filter(|card| card.section() == section)
+                .map(|card| format!("{} {}", card.quantity, name(card)))
+                .collect();
+            (!lines.is_empty()).then(|| format!("{section}\n{}\n", lines.join("\n")))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn render_csv(cards: &[ExportCard]) -> String {
+    let mut csv = String::from("quantity,name,section,oracle_id\n");
+    for card in cards {
+        csv.push_str(&format!(
+            "{},\"{}\",{},{}\n",
+            card.quantity,
+            card.name_full.replace('"', "\"\""),
+            card.section(),
+            card.oracle_id
+        ));
+    }
+    csv
+}
@@ -1,17 +1,56 @@
 #![allow(unused)]
 use axum::{
     debug_handler,
-    extract::{Path, State},
-    routing::get,
-    Json, Router,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
 };
+use archetype::ArchetypeParams;
+use average_deck::AverageDeckCard;
+use brawl_format::FormatParams;
+use card_query::CardQuery;
+use collection::CollectionParams;
+use deck_analysis::{AnalyzedCard, DeckAnalysis};
+use deck_export::{ExportCard, ExportParams};
+use error::ApiError;
+use extract::{Json, Path, Query};
+use pagination::{ListParams, Page, Pagination};
+use time_window::TimeWindowParams;
+use wildcards::{WildcardCost, WildcardFilter, WildcardParams};
 use serde::Deserialize;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
-use std::{env, net::SocketAddr};
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres, QueryBuilder};
+use std::{collections::HashMap, env, net::SocketAddr};
 use tower_http::cors::CorsLayer;
 use uuid::Uuid;
 use dotenv::dotenv;
 
+mod archetype;
+mod average_deck;
+mod brawl_format;
+mod card_query;
+mod collection;
+mod deck_analysis;
+mod deck_export;
+mod error;
+mod extract;
+mod pagination;
+mod time_window;
+mod validation;
+mod wildcards;
+
+const COMMANDER_SORTS: &[&str] = &["count", "name", "cmc"];
+const TOP_CARD_SORTS: &[&str] = &["rank", "decks", "name", "cmc"];
+const COMMANDER_TOP_CARD_SORTS: &[&str] = &["quantity", "synergy", "name", "cmc"];
+const SEARCH_SORTS: &[&str] = &["name", "cmc"];
+const CARD_PAIR_SORTS: &[&str] = &["decks", "lift", "name"];
+const SIMILAR_COMMANDER_SORTS: &[&str] = &["similarity", "shared", "name"];
+const RECOMMEND_SORTS: &[&str] = &["score", "synergy", "co_occurrence", "name"];
+const COLLECTION_COMMANDER_SORTS: &[&str] = &["owned", "decks", "name"];
+// A full Historic Brawl deck without its commander
+const MAX_RECOMMEND_CARDS: usize = 99;
+
 #[derive(Clone)]
 struct AppState {
     pool: Pool<Postgres>,
@@ -33,7 +72,14 @@ async fn main() {
         .route("/commander_slugs", get(commander_slugs))
         .route("/card_slugs", get(card_slugs))
         .route("/card/:slug", get(card_by_slug)) 
+        .route("/card/:slug/trend", get(card_trend))
+        .route("/card/:slug/pairs", get(card_pairs))
+        .route("/card/:slug/printings", get(card_printings))
         .route("/commander/:slug", get(commander_by_slug)) 
+        .route("/commander/:slug/trend", get(commander_trend))
+        .route("/commander/:slug/average-deck", get(commander_average_deck))
+        .route("/commander/:slug/similar", get(similar_commanders))
+        .route("/commander/:slug/profile", get(commander_profile))
         .route("/commanders/", get(top_commanders)) 
         .route("/commanders/:colors", get(top_commanders_of_color)) 
         .route("/commanders/colorless", get(top_commanders_colorless)) 
@@ -44,8 +90,15 @@ async fn main() {
             "/top_commanders_for_card/:slug",
             get(top_commanders_for_card),
         )
+        .route("/search", get(search_cards))
         .route("/search/:card_", get(get_card))
         .route("/deck/:deck_id", get(deck_by_id))
+        .route("/deck/:deck_id/export", get(export_deck))
+        .route("/decks/parse", post(parse_deck))
+        .route("/decks/validate", post(validate_deck))
+        .route("/recommend", post(recommend))
+        .route("/collection", post(import_collection))
+        .route("/collection/:collection_id/commanders", get(collection_commanders))
         .route("/health", get(health))
         .layer(CorsLayer::permissive())
         .with_state(state);
@@ -63,14 +116,47 @@ async fn health() -> &'static str {
     "OK"
 }
 
+// Splits a color path segment like "wub" into the colors a color identity must contain and the colors it must not
+// "colorless" is accepted as the empty color identity
+fn parse_color_identity(color: &str) -> Result<(Vec<String>, Vec<String>), ApiError> {
+    let mut not_colors = vec![
+        "W".to_string(),
+        "U".to_string(),
+        "B".to_string(),
+        "R".to_string(),
+        "G".to_string(),
+    ];
+    let mut colors = vec![];
+
+    if color.eq_ignore_ascii_case("colorless") {
+        return Ok((colors, not_colors));
+    }
+
+    for char in color.chars() {
+        let char = char.to_ascii_uppercase().to_string();
+        if !not_colors.contains(&char) && !colors.contains(&char) {
+            return Err(ApiError::bad_request(format!(
+                "invalid color '{char}' in '{color}', expected a combination of W, U, B, R and G"
+            )));
+        }
+        not_colors.retain(|x| &char != x);
+        if !colors.contains(&char) {
+            colors.push(char);
+        }
+    }
+
+    Ok((colors, not_colors))
+}
+
 
 #[axum::debug_handler]
 async fn deck_by_id(
     State(AppState { pool }): State<AppState>,
     Path(deck_id): Path<i32>,
-) -> Json<Deck> {
+) -> Result<Json<Deck>, ApiError> {
     #[derive(Debug)]
     struct DeckInfo {
+        id: i32,
         ah_deck_id: Option<i32>,
         url: String,
         username: String,
@@ -79,6 +165,7 @@ async fn deck_by_id(
         commander: Uuid,
         companion: Option<Uuid>,
         color_identity: Vec<String>,
+        format: String,
     }
 
     #[derive(serde::Serialize, Debug)]
@@ -106,6 +193,8 @@ async fn deck_by_id(
         colors_back: Option<Vec<String>>,
         color_identity: Vec<String>,
         is_legal: bool,
+        is_legal_standard: bool,
+        arena_id: Option<i32>,
         is_legal_commander: bool,
         is_rebalanced: bool,
         image_small: String,
@@ -126,14 +215,12 @@ async fn deck_by_id(
     let deck_info: DeckInfo = sqlx::query_as!(
         DeckInfo,
         "SELECT 
-            ah_deck_id, url, username, date_created, date_updated, commander, companion, color_identity 
+            id, ah_deck_id, url, username, date_created, date_updated, commander, companion, color_identity, format
             FROM deck 
-            WHERE ah_deck_id = $1;", deck_id).fetch_one(&pool).await.expect("couldn't fetch deck");
-
-    println!(
-        "commander uuid: {}\ncompanion uuid: {:#?}",
-        deck_info.commander, deck_info.companion
-    );
+            WHERE ah_deck_id = $1;", deck_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("no deck with id {deck_id}")))?;
 
     let commander: Card = sqlx::query_as!(
         Card,
@@ -141,22 +228,19 @@ async fn deck_by_id(
         deck_info.commander
     )
     .fetch_one(&pool)
-    .await
-    .expect("couldn't fetch commander by id");
+    .await?;
 
-    let companion: Option<Card> = if (deck_info.companion.is_some()) {
-        Some(
+    let companion: Option<Card> = match deck_info.companion {
+        Some(companion) => Some(
             sqlx::query_as!(
                 Card,
                 "SELECT * FROM card WHERE oracle_id = $1;",
-                deck_info.companion
+                companion
             )
             .fetch_one(&pool)
-            .await
-            .expect("couldn't fetch companion by id"),
-        )
-    } else {
-        None
+            .await?,
+        ),
+        None => None,
     };
 
     let deck_list: Vec<CardCount> = sqlx::query_as!(
@@ -171,8 +255,81 @@ async fn deck_by_id(
         deck_id
     )
     .fetch_all(&pool)
-    .await
-    .expect("couldn't fetch cards in deck");
+    .await?;
+
+    let mut wildcards = WildcardCost::default();
+    for card in &deck_list {
+        wildcards.add(
+            &card.lowest_rarity,
+            &card.type_line_front,
+            card.count.unwrap_or(1) as i32,
+        );
+    }
+
+    let analysis = deck_analysis::analyze(
+        &sqlx::query!(
+            "SELECT card.cmc, card.type_line_front, card.mana_cost_front, card.mana_cost_back,
+                card.oracle_text, decklist.quantity
+            FROM decklist
+            JOIN card ON card.oracle_id = decklist.oracle_id
+            WHERE decklist.deck_id = $1
+            AND NOT decklist.is_companion",
+            deck_info.id
+        )
+        .fetch_all(&pool)
+        .await?
+        .into_iter()
+        .map(|card| AnalyzedCard {
+            cmc: card.cmc,
+            type_line_front: card.type_line_front,
+            mana_cost_front: card.mana_cost_front,
+            mana_cost_back: card.mana_cost_back,
+            oracle_text: card.oracle_text,
+            quantity: card.quantity,
+        })
+        .collect::<Vec<AnalyzedCard>>(),
+    );
+
+    // The baseline analyzes every card played by the commander's decks in the same format, the deck itself included,
+    // summed over the decks so it's one row per card instead of one per decklist entry
+    let baseline_cards = sqlx::query!(
+        r#"SELECT card.cmc, card.type_line_front, card.mana_cost_front, card.mana_cost_back, card.oracle_text,
+            SUM(decklist.quantity)::int AS "quantity!",
+            (SELECT COUNT(DISTINCT decklist.deck_id)
+                FROM deck JOIN decklist ON decklist.deck_id = deck.id
+                WHERE deck.commander = $1 AND deck.format = $2) AS "decks!"
+        FROM deck
+        JOIN decklist ON decklist.deck_id = deck.id
+        JOIN card ON card.oracle_id = decklist.oracle_id
+        WHERE deck.commander = $1
+        AND deck.format = $2
+        AND NOT decklist.is_companion
+        GROUP BY card.oracle_id"#,
+        deck_info.commander,
+        deck_info.format
+    )
+    .fetch_all(&pool)
+    .await?;
+    let baseline_decks = baseline_cards.first().map_or(0, |card| card.decks);
+    let commander_baseline = CommanderBaseline {
+        decks: baseline_decks,
+        analysis: deck_analysis::per_deck(
+            deck_analysis::analyze(
+                &baseline_cards
+                    .into_iter()
+                    .map(|card| AnalyzedCard {
+                        cmc: card.cmc,
+                        type_line_front: card.type_line_front,
+                        mana_cost_front: card.mana_cost_front,
+                        mana_cost_back: card.mana_cost_back,
+                        oracle_text: card.oracle_text,
+                        quantity: card.quantity,
+                    })
+                    .collect::<Vec<AnalyzedCard>>(),
+            ),
+            baseline_decks,
+        ),
+    };
 
     let mut top_cards = Decklist {
         creatures: vec![],
@@ -233,21 +390,181 @@ async fn deck_by_id(
 
     let deck = Deck {
         //deck_id should really be NOT NULL in the database
-        deck_id: deck_info.ah_deck_id.unwrap(),
+        deck_id: deck_info.ah_deck_id.unwrap_or(deck_id),
         url: deck_info.url,
         username: deck_info.username,
         date_created: deck_info.date_created,
         date_updated: deck_info.date_updated,
-        commander: commander,
-        companion: companion,
+        commander,
+        companion,
         color_identity: deck_info.color_identity,
+        format: deck_info.format,
+        wildcards,
+        analysis,
+        commander_baseline,
         decklist: top_cards,
     };
 
-    Json(deck)
+    Ok(Json(deck))
+}
+
+// Returns the decklist as text that can be imported into Arena or Moxfield, or as CSV/JSON
+async fn export_deck(
+    State(AppState { pool }): State<AppState>,
+    Path(deck_id): Path<i32>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, ApiError> {
+    let format = params.format()?;
+
+    // The newest printing that's on Arena, so Arena imports the exact card
+    let cards = sqlx::query_as!(
+        ExportCard,
+        r#"SELECT card.oracle_id, card.name_full, card.name_front, card.layout,
+            decklist.quantity, decklist.is_commander, decklist.is_companion,
+            arena_printing.set_code AS "set_code?", arena_printing.collector_number AS "collector_number?"
+        FROM decklist
+        JOIN card ON card.oracle_id = decklist.oracle_id
+        JOIN deck ON deck.id = decklist.deck_id
+        LEFT JOIN LATERAL (
+            SELECT set_code, collector_number FROM printing
+            WHERE printing.oracle_id = card.oracle_id AND printing.arena_id IS NOT NULL
+            ORDER BY printing.released_at DESC
+            LIMIT 1
+        ) AS arena_printing ON true
+        WHERE deck.ah_deck_id = $1
+        ORDER BY card.name_full;"#,
+        deck_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    if cards.is_empty() {
+        return Err(ApiError::not_found(format!("no deck with id {deck_id}")));
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, format.content_type())],
+        deck_export::render(&cards, format),
+    )
+        .into_response())
+}
+
+// Resolves a decklist pasted from MTG Arena's export to the cards in the database
+// Returns the cards that were found and the lines that couldn't be matched to a card
+async fn resolve_decklist(
+    pool: &Pool<Postgres>,
+    text: &str,
+) -> Result<(Vec<ResolvedCard>, Vec<String>), ApiError> {
+    let decklist = decklist::parse_arena_export(text);
+    if decklist.cards.is_empty() {
+        return Err(ApiError::bad_request("no cards found in the decklist"));
+    }
+
+    let mut cards = vec![];
+    let mut unresolved = decklist.unparsed;
+
+    // Same lookup the migration tool uses, DFCs are exported by their front face name only
+    // The card table only keeps the alchemy version of a rebalanced card, so `A-` names are looked up first
+    let names: Vec<String> = decklist
+        .cards
+        .iter()
+        .flat_map(|card| [card.a_name.clone(), card.name.clone()])
+        .collect();
+    let oracle_ids: HashMap<String, String> = sqlx::query!(
+        r#"SELECT DISTINCT ON (lookup) lookup AS "lookup!", card.oracle_id::text AS "oracle_id!"
+        FROM unnest($1::text[]) AS lookup
+        JOIN card ON unaccent(card.name_full) = unaccent(lookup)
+            OR (unaccent(card.name_front) = unaccent(lookup) AND card.layout IN ('transform','modal_dfc', 'adventure'))
+        ORDER BY lookup, unaccent(card.name_full) = unaccent(lookup) DESC"#,
+        &names
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.lookup, row.oracle_id))
+    .collect();
+
+    let resolved: Vec<String> = oracle_ids.values().cloned().collect();
+    let card_rows: HashMap<String, Card> = sqlx::query_as!(
+        Card,
+        "SELECT * FROM card WHERE oracle_id::text = ANY($1)",
+        &resolved
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|card| (card.oracle_id.clone(), card))
+    .collect();
+
+    for card in decklist.cards {
+        let res = oracle_ids
+            .get(&card.a_name)
+            .or_else(|| oracle_ids.get(&card.name))
+            .and_then(|oracle_id| card_rows.get(oracle_id).cloned());
+
+        match res {
+            Some(res) => cards.push(ResolvedCard {
+                card: res,
+                quantity: card.quantity.unwrap_or(1),
+                is_commander: card.is_commander,
+                is_companion: card.is_companion,
+            }),
+            None => unresolved.push(card.name),
+        }
+    }
+
+    Ok((cards, unresolved))
+}
+
+async fn parse_deck(
+    State(AppState { pool }): State<AppState>,
+    body: String,
+) -> Result<Json<ParsedDeck>, ApiError> {
+    let (cards, unresolved) = resolve_decklist(&pool, &body).await?;
+    let cards: Vec<ParsedCard> = cards
+        .into_iter()
+        .map(|card| ParsedCard {
+            oracle_id: card.card.oracle_id,
+            name: card.card.name_full,
+            slug: card.card.slug,
+            quantity: card.quantity,
+            color_identity: card.card.color_identity,
+            is_commander: card.is_commander,
+            is_companion: card.is_companion,
+        })
+        .collect();
+
+    let commander = cards.iter().find(|card| card.is_commander).cloned();
+    let companion = cards.iter().find(|card| card.is_companion).cloned();
+    let color_identity = commander
+        .as_ref()
+        .map(|commander| commander.color_identity.clone())
+        .unwrap_or_default();
+
+    Ok(Json(ParsedDeck {
+        commander,
+        companion,
+        color_identity,
+        cards,
+        unresolved,
+    }))
+}
+
+// Checks a decklist in MTG Arena's export format against the Brawl deckbuilding rules
+// Without `?format=` the deck is checked as a 60 card deck against the Historic Brawl card pool
+async fn validate_deck(
+    Query(format): Query<FormatParams>,
+    State(AppState { pool }): State<AppState>,
+    body: String,
+) -> Result<Json<validation::DeckValidation>, ApiError> {
+    let format = format.format()?;
+    let (cards, unresolved) = resolve_decklist(&pool, &body).await?;
+    Ok(Json(validation::validate_deck(&cards, &unresolved, format)))
 }
 
-async fn card_slugs(State(AppState { pool }): State<AppState>) -> Json<Vec<Option<String>>> {
+async fn card_slugs(
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Vec<Option<String>>>, ApiError> {
     struct Response {
         slug: Option<String>,
     }
@@ -257,19 +574,20 @@ async fn card_slugs(State(AppState { pool }): State<AppState>) -> Json<Vec<Optio
         "SELECT DISTINCT slug FROM card WHERE is_legal=true"
     )
     .fetch_all(&pool)
-    .await
-    .expect("couldn't fetch card slugs")
+    .await?
     .into_iter()
     .map(|res| res.slug)
     .collect();
-    Json(res)
+    Ok(Json(res))
 }
 
 #[axum::debug_handler]
 async fn card_by_slug(
     State(AppState { pool }): State<AppState>,
     Path(slug): Path<String>,
-) -> Json<TopCards> {
+    Query(format): Query<FormatParams>,
+) -> Result<Json<TopCards>, ApiError> {
+    let format = format.format()?;
     let res = sqlx::query_as!(
         TopCards,
         r#"
@@ -279,12 +597,15 @@ async fn card_by_slug(
                 (
                     SELECT COUNT(DISTINCT decklist.deck_id)
                     FROM decklist
+                    JOIN deck ON deck.id = decklist.deck_id
                     WHERE decklist.oracle_id = card.oracle_id
+                    AND ($2::text IS NULL OR deck.format = $2)
                 ) AS "total_decks_with_card!",
                 (
                     SELECT COUNT(DISTINCT deck.id)
                     FROM deck
                     WHERE deck.color_identity @> card.color_identity
+                    AND ($2::text IS NULL OR deck.format = $2)
                 ) AS "total_decks_could_play!"
             FROM
                 card
@@ -303,50 +624,68 @@ async fn card_by_slug(
             card
         JOIN
             CardCounts cc ON card.oracle_id = cc.oracle_id;"#,
-        slug
+        slug,
+        format
     )
-    .fetch_one(&pool)
-    .await
-    .expect("couldn't fetch card by slug");
-    Json(res)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no card with slug '{slug}'")))?;
+    Ok(Json(res))
 }
 
 async fn commander_by_slug(
     State(AppState { pool }): State<AppState>,
     Path(slug): Path<String>,
-) -> Json<CardSlug> {
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+    Query(archetype): Query<ArchetypeParams>,
+) -> Result<Json<CommanderSlug>, ApiError> {
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+    let archetype = archetype.archetype()?;
+    // The deck counts and rank are always across all of the commander's archetypes, so they share a denominator
+    // `?archetype=N` only selects the archetype, its own deck count is in `archetype.decks`
     let res = sqlx::query_as!(
         CardSlug,
         "SELECT card.*, total_decks, all_decks, rank, total_commander_decks_of_ci FROM card
         JOIN (
             SELECT COUNT(commander) as all_decks FROM deck
+            WHERE date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
         ) as d1 ON true
         JOIN (
             SELECT COUNT(commander) as total_decks, commander FROM deck
+            WHERE date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
             GROUP BY commander
         ) AS d ON card.oracle_id = d.commander
         JOIN (
             SELECT commander, row_number() OVER (ORDER BY COUNT(commander) DESC) rank FROM deck
+            WHERE date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
             GROUP BY commander
         ) AS commander_rank ON commander_rank.commander = card.oracle_id
         JOIN (
             SELECT COUNT(*) AS total_commander_decks_of_ci FROM deck
             WHERE color_identity = (SELECT color_identity FROM card WHERE slug = $1 LIMIT 1)
+            AND date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
         ) AS total_commander_decks_of_ci ON true
         WHERE card.slug = $1;",
-        slug
+        slug,
+        updated_since,
+        format
     )
     .fetch_optional(&pool)
-    .await
-    .expect("couldn't fetch commander by slug");
+    .await?;
 
     let res = match res {
         Some(card) => card,
         None => {
             let card = sqlx::query_as!(Card, "SELECT * FROM card WHERE slug = $1", slug)
-                .fetch_one(&pool)
-                .await
-                .expect("Couldn't fetch commander by slug");
+                .fetch_optional(&pool)
+                .await?
+                .ok_or_else(|| ApiError::not_found(format!("no commander with slug '{slug}'")))?;
             CardSlug {
                 all_decks: Some(0),
                 cmc: card.cmc,
@@ -358,6 +697,8 @@ async fn commander_by_slug(
                 image_normal: card.image_normal,
                 image_small: card.image_small,
                 is_legal: card.is_legal,
+                is_legal_standard: card.is_legal_standard,
+                arena_id: card.arena_id,
                 is_legal_commander: card.is_legal_commander,
                 lang: card.lang,
                 layout: card.layout,
@@ -390,12 +731,41 @@ async fn commander_by_slug(
             }
         }
     };
-    Json(res)
+
+    let archetypes = sqlx::query_as!(
+        Archetype,
+        "SELECT archetype, label, decks FROM commander_archetype
+        WHERE commander = $1::text::uuid
+        ORDER BY archetype",
+        res.oracle_id
+    )
+    .fetch_all(&pool)
+    .await?;
+    let selected_archetype = match archetype {
+        Some(archetype) => Some(
+            archetypes
+                .iter()
+                .find(|a| a.archetype == archetype)
+                .cloned()
+                .ok_or_else(|| {
+                    ApiError::not_found(format!("no archetype {archetype} for commander '{slug}'"))
+                })?,
+        ),
+        None => None,
+    };
+
+    Ok(Json(CommanderSlug {
+        card: res,
+        archetype: selected_archetype,
+        archetypes,
+    }))
 }
 
 
 // Returns slugified names of all legal commanders in the database
-async fn commander_slugs(State(AppState { pool }): State<AppState>) -> Json<Vec<Option<String>>> {
+async fn commander_slugs(
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Vec<Option<String>>>, ApiError> {
     struct Response {
         slug: Option<String>,
     }
@@ -405,56 +775,78 @@ async fn commander_slugs(State(AppState { pool }): State<AppState>) -> Json<Vec<
         "SELECT DISTINCT slug FROM card WHERE is_legal_commander=true"
     )
     .fetch_all(&pool)
-    .await
-    .expect("couldn't fetch slugs")
+    .await?
     .into_iter()
     .map(|slug| slug.slug)
     .collect();
 
-    Json(res)
+    Ok(Json(res))
 }
 
 // Returns card info for all colorless commanders ordered by number of decks helmed by that commander
 async fn top_commanders_colorless(
+    Query(params): Query<ListParams>,
+    Query(format): Query<FormatParams>,
     State(AppState { pool }): State<AppState>,
-) -> Json<Vec<CardCount>> {
+) -> Result<Json<Page<Vec<CardCount>>>, ApiError> {
+    let pagination = params.resolve(COMMANDER_SORTS, 100)?;
+    let format = format.format()?;
     let res = sqlx::query_as!(
         CardCount,
         "SELECT c.*, COUNT(d.commander) as count
     FROM card c
-    LEFT JOIN deck d ON c.oracle_id = d.commander
+    LEFT JOIN deck d ON c.oracle_id = d.commander AND ($5::text IS NULL OR d.format = $5)
     WHERE c.is_legal_commander = TRUE
     -- AND c.is_legal=TRUE
+    AND ($5::text IS NULL OR $5 <> 'standard' OR c.is_legal_standard)
     AND (c.color_identity = '{}'::char(1)[])
     GROUP BY c.oracle_id
-    ORDER BY count DESC
-    "
+    ORDER BY
+        CASE WHEN $1 = 'count' AND $2 THEN COUNT(d.commander) END DESC,
+        CASE WHEN $1 = 'count' AND NOT $2 THEN COUNT(d.commander) END ASC,
+        CASE WHEN $1 = 'cmc' AND $2 THEN c.cmc END DESC,
+        CASE WHEN $1 = 'cmc' AND NOT $2 THEN c.cmc END ASC,
+        CASE WHEN $1 = 'name' AND $2 THEN c.name_full END DESC,
+        CASE WHEN $1 = 'name' AND NOT $2 THEN c.name_full END ASC,
+        c.name_full
+    LIMIT $3 OFFSET $4
+    ",
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset(),
+        format
     )
     .fetch_all(&pool)
-    .await
-    .expect("error retrieving from db");
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "total!" FROM card c
+        WHERE c.is_legal_commander = TRUE
+        AND ($1::text IS NULL OR $1 <> 'standard' OR c.is_legal_standard)
+        AND (c.color_identity = '{}'::char(1)[])"#,
+        format
+    )
+    .fetch_one(&pool)
+    .await?;
 
-    Json(res)
+    Ok(Json(Page::new(res, total, &pagination)))
 }
 
 #[axum::debug_handler]
 async fn top_cards_of_color(
     Path(color): Path<String>,
+    Query(params): Query<ListParams>,
+    Query(format): Query<FormatParams>,
     State(AppState { pool }): State<AppState>,
-) -> Json<Vec<TopCards>> {
-
-    let mut not_colors = vec![
-        "W".to_string(),
-        "U".to_string(),
-        "B".to_string(),
-        "R".to_string(),
-        "G".to_string(),
-    ];
-    let mut colors = vec![];
-
-    for char in color.chars() {
-        not_colors.retain(|x| &char.to_ascii_uppercase().to_string() != x);
-        colors.push(char.to_uppercase().to_string());
+) -> Result<Json<Page<Vec<TopCards>>>, ApiError> {
+    let (colors, not_colors) = parse_color_identity(&color)?;
+    let pagination = params.resolve(TOP_CARD_SORTS, 100)?;
+    let format = format.format()?;
+
+    // The top_cards table is built from decks of every format
+    if format.is_some() {
+        return top_cards_live(&pool, &pagination, &colors, &not_colors, 0, format).await;
     }
 
     let res = sqlx::query_as!(
@@ -469,125 +861,173 @@ async fn top_cards_of_color(
         ON top_cards.oracle_id = card.oracle_id
         WHERE (top_cards.color_identity @> $1::char(1)[])
         AND NOT (top_cards.color_identity && $2::char(1)[])
-        ORDER BY rank DESC
-        LIMIT 100;
+        ORDER BY
+            CASE WHEN $3 = 'rank' AND $4 THEN rank END DESC,
+            CASE WHEN $3 = 'rank' AND NOT $4 THEN rank END ASC,
+            CASE WHEN $3 = 'decks' AND $4 THEN total_decks_with_card END DESC,
+            CASE WHEN $3 = 'decks' AND NOT $4 THEN total_decks_with_card END ASC,
+            CASE WHEN $3 = 'cmc' AND $4 THEN card.cmc END DESC,
+            CASE WHEN $3 = 'cmc' AND NOT $4 THEN card.cmc END ASC,
+            CASE WHEN $3 = 'name' AND $4 THEN card.name_full END DESC,
+            CASE WHEN $3 = 'name' AND NOT $4 THEN card.name_full END ASC,
+            total_decks_with_card DESC
+        LIMIT $5 OFFSET $6;
         ",
         &colors,
-        &not_colors
+        &not_colors,
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset()
     )
     .fetch_all(&pool)
-    .await
-    .expect("error with db");
-    Json(res)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "total!" FROM top_cards
+        WHERE (top_cards.color_identity @> $1::char(1)[])
+        AND NOT (top_cards.color_identity && $2::char(1)[])"#,
+        &colors,
+        &not_colors
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page::new(res, total, &pagination)))
 }
 
 async fn top_commanders_of_color(
     Path(color): Path<String>,
+    Query(params): Query<ListParams>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
     State(AppState { pool }): State<AppState>,
-) -> Json<Vec<CardCount>> {
+) -> Result<Json<Page<Vec<CardCount>>>, ApiError> {
     //Used to display the top commanders of a specific color identity, ordered by the number of decks with this commander
-    let mut not_colors = vec![
-        "W".to_string(),
-        "U".to_string(),
-        "B".to_string(),
-        "R".to_string(),
-        "G".to_string(),
-    ];
-    let mut colors = vec![];
-
-    for char in color.chars() {
-        not_colors.retain(|x| &char.to_ascii_uppercase().to_string() != x);
-        colors.push(char.to_uppercase().to_string());
-    }
-    // println!("colors: {:#?} \n not_colors = {:#?}", colors, not_colors);
+    let (colors, not_colors) = parse_color_identity(&color)?;
+    let pagination = params.resolve(COMMANDER_SORTS, 100)?;
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
 
     let res = sqlx::query_as!(
         CardCount,
         "SELECT c.*, COUNT(d.commander) AS count
         FROM card c
-        LEFT JOIN deck d ON c.oracle_id = d.commander
+        LEFT JOIN deck d ON c.oracle_id = d.commander AND d.date_updated >= $7
+            AND ($8::text IS NULL OR d.format = $8)
         WHERE c.is_legal_commander = TRUE
         -- AND c.is_legal=TRUE
+        AND ($8::text IS NULL OR $8 <> 'standard' OR c.is_legal_standard)
         AND c.color_identity @> $1::char(1)[]  -- Checks if it contains all colors in 'colors'
         AND NOT c.color_identity && $2::char(1)[]  -- Checks if it intersects with 'not_colors'
         GROUP BY c.oracle_id
-        ORDER BY count DESC;
+        ORDER BY
+            CASE WHEN $3 = 'count' AND $4 THEN COUNT(d.commander) END DESC,
+            CASE WHEN $3 = 'count' AND NOT $4 THEN COUNT(d.commander) END ASC,
+            CASE WHEN $3 = 'cmc' AND $4 THEN c.cmc END DESC,
+            CASE WHEN $3 = 'cmc' AND NOT $4 THEN c.cmc END ASC,
+            CASE WHEN $3 = 'name' AND $4 THEN c.name_full END DESC,
+            CASE WHEN $3 = 'name' AND NOT $4 THEN c.name_full END ASC,
+            c.name_full
+        LIMIT $5 OFFSET $6;
         ",
         &colors,
-        &not_colors
+        &not_colors,
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset(),
+        updated_since,
+        format
     )
     .fetch_all(&pool)
-    .await
-    .expect("error retrieving from db");
-
-    Json(res)
-}
-
-async fn top_commanders_of_color_time(
-    Path((color, time)): Path<(String, String)>,
-    State(AppState { pool }): State<AppState>,
-) -> Json<Vec<CardCount>> {
-    let mut not_colors = vec![
-        "W".to_string(),
-        "U".to_string(),
-        "B".to_string(),
-        "R".to_string(),
-        "G".to_string(),
-    ];
-    let mut colors = vec![];
-
-    for char in color.chars() {
-        not_colors.retain(|x| &char.to_ascii_uppercase().to_string() != x);
-        colors.push(char.to_uppercase().to_string());
-    }
-    println!("colors: {:#?} \n not_colors = {:#?}", colors, not_colors);
+    .await?;
 
-    let res = sqlx::query_as!(
-        CardCount,
-        "SELECT c.*, COUNT(d.commander) AS count
-        FROM card c
-        LEFT JOIN deck d ON c.oracle_id = d.commander
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "total!" FROM card c
         WHERE c.is_legal_commander = TRUE
-        -- AND c.is_legal=TRUE
-        AND c.color_identity @> $1::char(1)[]  -- Checks if it contains all colors in 'colors'
-        AND NOT c.color_identity && $2::char(1)[]  -- Checks if it intersects with 'not_colors'
-        GROUP BY c.oracle_id
-        ORDER BY count DESC;
-        ",
+        AND ($3::text IS NULL OR $3 <> 'standard' OR c.is_legal_standard)
+        AND c.color_identity @> $1::char(1)[]
+        AND NOT c.color_identity && $2::char(1)[]"#,
         &colors,
-        &not_colors
+        &not_colors,
+        format
     )
-    .fetch_all(&pool)
-    .await
-    .expect("error retrieving from db");
+    .fetch_one(&pool)
+    .await?;
 
-    Json(res)
+    Ok(Json(Page::new(res, total, &pagination)))
 }
 
 #[debug_handler]
-async fn top_commanders(State(AppState { pool }): State<AppState>) -> Json<Vec<CardCount>> {
+async fn top_commanders(
+    Query(params): Query<ListParams>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Page<Vec<CardCount>>>, ApiError> {
     // Used to display every commander, ordered number of decks with this commander
+    let pagination = params.resolve(COMMANDER_SORTS, 100)?;
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
     let res = sqlx::query_as!(
         CardCount,
         "SELECT c.*, COUNT(d.commander) AS count
         FROM card c
-        LEFT JOIN deck d ON c.oracle_id = d.commander
+        LEFT JOIN deck d ON c.oracle_id = d.commander AND d.date_updated >= $5
+            AND ($6::text IS NULL OR d.format = $6)
         WHERE c.is_legal_commander = TRUE
+        AND ($6::text IS NULL OR $6 <> 'standard' OR c.is_legal_standard)
         GROUP BY c.oracle_id
-        ORDER BY count DESC
-        LIMIT 100;"
+        ORDER BY
+            CASE WHEN $1 = 'count' AND $2 THEN COUNT(d.commander) END DESC,
+            CASE WHEN $1 = 'count' AND NOT $2 THEN COUNT(d.commander) END ASC,
+            CASE WHEN $1 = 'cmc' AND $2 THEN c.cmc END DESC,
+            CASE WHEN $1 = 'cmc' AND NOT $2 THEN c.cmc END ASC,
+            CASE WHEN $1 = 'name' AND $2 THEN c.name_full END DESC,
+            CASE WHEN $1 = 'name' AND NOT $2 THEN c.name_full END ASC,
+            c.name_full
+        LIMIT $3 OFFSET $4;",
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset(),
+        updated_since,
+        format
     )
     .fetch_all(&pool)
-    .await
-    .expect("error retrieving from db");
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "total!" FROM card
+        WHERE is_legal_commander = TRUE
+        AND ($1::text IS NULL OR $1 <> 'standard' OR is_legal_standard)"#,
+        format
+    )
+    .fetch_one(&pool)
+    .await?;
 
-    Json(res)
+    Ok(Json(Page::new(res, total, &pagination)))
 }
 
 #[debug_handler]
-async fn top_cards(State(AppState { pool }): State<AppState>) -> Json<Vec<TopCards>> {
+async fn top_cards(
+    Query(params): Query<ListParams>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Page<Vec<TopCards>>>, ApiError> {
     // Used to display the top cards, ordered by the number of decks the card appears in
     // FIX: Should be ordered by (number of decks the card appears in / number of decks the card CAN appear in)
+    let pagination = params.resolve(TOP_CARD_SORTS, 100)?;
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+
+    // The top_cards table only holds all-time numbers across every format, so filtered requests are computed from the decks directly
+    if updated_since > 0 || format.is_some() {
+        return top_cards_live(&pool, &pagination, &[], &[], updated_since, format).await;
+    }
+
     let res = sqlx::query_as!(
         TopCards,
         "SELECT
@@ -598,60 +1038,153 @@ async fn top_cards(State(AppState { pool }): State<AppState>) -> Json<Vec<TopCar
     FROM top_cards
     JOIN card
     ON top_cards.oracle_id = card.oracle_id
-    ORDER BY (total_decks_with_card * 100 / NULLIF(total_decks_could_play, 0)) DESC,
+    ORDER BY
+        CASE WHEN $1 = 'rank' AND $2 THEN rank END DESC,
+        CASE WHEN $1 = 'rank' AND NOT $2 THEN rank END ASC,
+        CASE WHEN $1 = 'decks' AND $2 THEN total_decks_with_card END DESC,
+        CASE WHEN $1 = 'decks' AND NOT $2 THEN total_decks_with_card END ASC,
+        CASE WHEN $1 = 'cmc' AND $2 THEN card.cmc END DESC,
+        CASE WHEN $1 = 'cmc' AND NOT $2 THEN card.cmc END ASC,
+        CASE WHEN $1 = 'name' AND $2 THEN card.name_full END DESC,
+        CASE WHEN $1 = 'name' AND NOT $2 THEN card.name_full END ASC,
     total_decks_with_card DESC
-    LIMIT 100;
-          "
+    LIMIT $3 OFFSET $4;
+          ",
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset()
     )
     .fetch_all(&pool)
-    .await
-    .expect("error querying db");
+    .await?;
+
+    let total = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "total!" FROM top_cards"#)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok(Json(Page::new(res, total, &pagination)))
+}
+
+// Same ranking as the top_cards table, computed from the decks matching the filters
+// An empty `colors` and `not_colors` includes cards of every color identity
+async fn top_cards_live(
+    pool: &Pool<Postgres>,
+    pagination: &Pagination,
+    colors: &[String],
+    not_colors: &[String],
+    updated_since: i64,
+    format: Option<&str>,
+) -> Result<Json<Page<Vec<TopCards>>>, ApiError> {
+    let res = sqlx::query_as!(
+        TopCards,
+        r#"WITH WindowDecks AS (
+            SELECT id, color_identity FROM deck
+            WHERE date_updated >= $1
+            AND ($6::text IS NULL OR format = $6)
+        ),
+        CardCounts AS (
+            SELECT
+                decklist.oracle_id,
+                COUNT(DISTINCT decklist.deck_id) AS total_decks_with_card
+            FROM decklist
+            JOIN WindowDecks ON WindowDecks.id = decklist.deck_id
+            GROUP BY decklist.oracle_id
+        ),
+        RankedCards AS (
+            SELECT
+                card.oracle_id,
+                cc.total_decks_with_card,
+                (
+                    SELECT COUNT(*)
+                    FROM WindowDecks
+                    WHERE WindowDecks.color_identity @> card.color_identity
+                ) AS total_decks_could_play
+            FROM card
+            JOIN CardCounts cc ON cc.oracle_id = card.oracle_id
+            WHERE card.color_identity @> $7::char(1)[]
+            AND NOT card.color_identity && $8::char(1)[]
+        )
+        SELECT
+            card.*,
+            rc.total_decks_could_play::int AS "total_decks_could_play!",
+            rc.total_decks_with_card::int AS "total_decks_with_card!",
+            CASE
+                WHEN rc.total_decks_could_play = 0 THEN 0
+                ELSE (rc.total_decks_with_card * 100.0 / rc.total_decks_could_play)::float
+            END AS "rank!"
+        FROM card
+        JOIN RankedCards rc ON rc.oracle_id = card.oracle_id
+        ORDER BY
+            CASE WHEN $2 = 'rank' AND $3 THEN rc.total_decks_with_card * 100.0 / NULLIF(rc.total_decks_could_play, 0) END DESC,
+            CASE WHEN $2 = 'rank' AND NOT $3 THEN rc.total_decks_with_card * 100.0 / NULLIF(rc.total_decks_could_play, 0) END ASC,
+            CASE WHEN $2 = 'decks' AND $3 THEN rc.total_decks_with_card END DESC,
+            CASE WHEN $2 = 'decks' AND NOT $3 THEN rc.total_decks_with_card END ASC,
+            CASE WHEN $2 = 'cmc' AND $3 THEN card.cmc END DESC,
+            CASE WHEN $2 = 'cmc' AND NOT $3 THEN card.cmc END ASC,
+            CASE WHEN $2 = 'name' AND $3 THEN card.name_full END DESC,
+            CASE WHEN $2 = 'name' AND NOT $3 THEN card.name_full END ASC,
+            rc.total_decks_with_card DESC
+        LIMIT $4 OFFSET $5;"#,
+        updated_since,
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset(),
+        format,
+        colors,
+        not_colors
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(DISTINCT decklist.oracle_id) AS "total!" FROM decklist
+        JOIN deck ON deck.id = decklist.deck_id
+        JOIN card ON card.oracle_id = decklist.oracle_id
+        WHERE deck.date_updated >= $1
+        AND ($2::text IS NULL OR deck.format = $2)
+        AND card.color_identity @> $3::char(1)[]
+        AND NOT card.color_identity && $4::char(1)[]"#,
+        updated_since,
+        format,
+        colors,
+        not_colors
+    )
+    .fetch_one(pool)
+    .await?;
 
-    Json(res)
+    Ok(Json(Page::new(res, total, pagination)))
 }
 
 // TODO Clean this up
+// Every type bucket is sliced with the same page and per_page, so unlike other list endpoints `total` isn't
+// the number of cards in `items`. It's the size of the largest bucket, so `next_page` is set while any bucket has more
+#[allow(clippy::too_many_arguments)]
 async fn commander_top_cards(
     Path(oracle_id): Path<String>,
+    Query(params): Query<ListParams>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+    Query(archetype): Query<ArchetypeParams>,
+    Query(wildcards): Query<WildcardParams>,
+    Query(collection): Query<CollectionParams>,
     State(AppState { pool }): State<AppState>,
-) -> Json<TopCardsForCommander> {
-    let top_cards_for_commander = sqlx::query_as!(
-        CommanderTopCard,
-        "SELECT card.*, quantity, total_commander_decks, ci_quantity, total_commander_decks_of_ci FROM card
-        JOIN (
-            SELECT oracle_id, COUNT(oracle_id) as quantity FROM decklist
-            LEFT JOIN deck ON decklist.deck_id = deck.id
-            WHERE commander = $1 AND oracle_id <> $1
-            GROUP BY oracle_id
-        ) AS card_quantity ON card_quantity.oracle_id = card.oracle_id
-        JOIN (
-            SELECT COUNT(*) AS total_commander_decks
-                FROM deck
-                WHERE commander = $1
-        ) AS total_commander_decks ON true
-        JOIN (
-            SELECT oracle_id, COUNT(oracle_id) as ci_quantity FROM decklist
-            LEFT JOIN deck ON decklist.deck_id = deck.id
-            WHERE color_identity = (SELECT color_identity FROM card WHERE oracle_id = $1)
-            GROUP BY oracle_id
-        ) AS ci_card_quantity ON ci_card_quantity.oracle_id = card.oracle_id
-        JOIN (
-            SELECT COUNT(*) AS total_commander_decks_of_ci
-            FROM deck
-            WHERE color_identity = (SELECT color_identity FROM card WHERE oracle_id = $1)
-        ) AS total_commander_decks_of_ci ON true
-        WHERE card.type_line_full NOT LIKE 'Basic Land%'
-        ORDER BY quantity DESC
-        LIMIT 1000;",
-        Uuid::parse_str(&oracle_id).expect("uuid parsed wrong"),
-    )
-    .fetch_all(&pool)
-    .await
-    .expect("error querying db");
-
-    let top_cards_for_commander = top_cards_for_commander
-        .iter()
-        .map(CommanderTopCardWithSynergy::add_synergy);
+) -> Result<Json<Page<TopCardsForCommander>>, ApiError> {
+    let oracle_id = Uuid::parse_str(&oracle_id)?;
+    let pagination = params.resolve(COMMANDER_TOP_CARD_SORTS, 50)?;
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+    let archetype = archetype.archetype()?;
+    let wildcards = wildcards.filter()?;
+    let mut top_cards_for_commander =
+        commander_cards_with_synergy(&pool, oracle_id, updated_since, format, archetype).await?;
+    CommanderTopCardWithSynergy::sort(&mut top_cards_for_commander, &pagination);
+    if let Some(collection) = collection.collection {
+        let owned = owned_cards(&pool, collection).await?;
+        for card in &mut top_cards_for_commander {
+            card.owned = Some(owned.get(&card.oracle_id).copied().unwrap_or_default());
+        }
+    }
 
     let mut top_cards = TopCardsForCommander {
         creatures: vec![],
@@ -665,128 +1198,394 @@ async fn commander_top_cards(
     };
 
     fn is_mana_artifact(card: &CommanderTopCardWithSynergy) -> bool {
+        let oracle_text = card
+            .oracle_text
+            .as_deref()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
         card.type_line_full
             .to_ascii_lowercase()
             .contains("artifact")
-            && card
-                .oracle_text
-                .clone()
-                .expect("missing oracle text")
-                .to_ascii_lowercase()
-                .contains("add")
-            && card
-                .oracle_text
-                .clone()
-                .expect("missing oracle text")
-                .to_ascii_lowercase()
-                .contains("mana")
+            && oracle_text.contains("add")
+            && oracle_text.contains("mana")
     }
 
     for card in top_cards_for_commander.into_iter() {
         match &card {
-            t if t.type_line_front.to_ascii_lowercase().contains("creature")
-                && top_cards.creatures.len() < 50 =>
-            {
+            t if t.type_line_front.to_ascii_lowercase().contains("creature") => {
                 top_cards.creatures.push(card)
             }
-            t if t.type_line_front.to_ascii_lowercase().contains("instant")
-                && top_cards.instants.len() < 50 =>
-            {
+            t if t.type_line_front.to_ascii_lowercase().contains("instant") => {
                 top_cards.instants.push(card)
             }
-            t if t.type_line_front.to_ascii_lowercase().contains("sorcery")
-                && top_cards.sorceries.len() < 50 =>
-            {
+            t if t.type_line_front.to_ascii_lowercase().contains("sorcery") => {
                 top_cards.sorceries.push(card)
             }
             t if t
                 .type_line_front
                 .to_ascii_lowercase()
-                .contains("planeswalker")
-                && top_cards.planeswalkers.len() < 50 =>
+                .contains("planeswalker") =>
             {
                 top_cards.planeswalkers.push(card)
             }
             t if t
                 .type_line_front
                 .to_ascii_lowercase()
-                .contains("enchantment")
-                && top_cards.enchantments.len() < 50 =>
+                .contains("enchantment") =>
             {
                 top_cards.enchantments.push(card)
             }
-            t if t.type_line_front.to_ascii_lowercase().contains("land")
-                && top_cards.lands.len() < 50 =>
-            {
+            t if t.type_line_front.to_ascii_lowercase().contains("land") => {
                 top_cards.lands.push(card)
             }
-            t if is_mana_artifact(&t) => top_cards.mana_artifacts.push(card),
-            t if t.type_line_front.to_ascii_lowercase().contains("artifact")
-                && top_cards.utility_artifacts.len() < 50 =>
-            {
+            t if is_mana_artifact(t) => top_cards.mana_artifacts.push(card),
+            t if t.type_line_front.to_ascii_lowercase().contains("artifact") => {
                 top_cards.utility_artifacts.push(card)
             }
             _ => (),
         };
     }
 
-    Json(top_cards)
-}
-
-// return the front face name the of flip cards
-#[derive(Debug, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SearchResults {
-    card_name: String,
-    image: String,
-    slug: String,
+    // The budget is per page of every type, so it's applied once the cards are split into types
+    let top_cards = top_cards.filter_wildcards(&wildcards, pagination.per_page as usize);
+    let total = top_cards.largest_bucket() as i64;
+    Ok(Json(Page::new(top_cards.paginate(&pagination), total, &pagination)))
 }
-async fn get_card(
-    Path(card_name): Path<String>,
-    State(AppState { pool }): State<AppState>,
-) -> Json<Vec<SearchResults>> {
-    struct Response {
-        name_full: String,
-        image_art_crop: String,
-        slug: Option<String>,
-        is_legal_commander: bool,
-    }
-    let res = sqlx::query_as!(
-        Response,
-        "SELECT name_full, image_art_crop, slug, is_legal_commander FROM card WHERE is_legal = true AND name_full ILIKE $1 LIMIT 20",
-        format!("%{}%", card_name)
-    )
-    .fetch_all(&pool)
-    .await
-    .expect("couldn't fetch card");
 
-    fn get_route(is_commander: bool, slug: String) -> String {
-        if is_commander {
-            format!("/commander/{}", slug)
-        } else {
-            format!("/card/{}", slug)
-        }
-    }
+// Every card played with the commander, with its synergy compared to decks of the same color identity
+async fn commander_cards_with_synergy(
+    pool: &Pool<Postgres>,
+    oracle_id: Uuid,
+    updated_since: i64,
+    format: Option<&str>,
+    archetype: Option<i32>,
+) -> Result<Vec<CommanderTopCardWithSynergy>, ApiError> {
+    let cards = sqlx::query_as!(
+        CommanderTopCard,
+        "SELECT card.*, quantity, total_commander_decks, ci_quantity, total_commander_decks_of_ci FROM card
+        JOIN (
+            SELECT oracle_id, COUNT(oracle_id) as quantity FROM decklist
+            LEFT JOIN deck ON decklist.deck_id = deck.id
+            WHERE commander = $1 AND oracle_id <> $1
+            AND date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
+            AND ($4::int IS NULL OR archetype = $4)
+            GROUP BY oracle_id
+        ) AS card_quantity ON card_quantity.oracle_id = card.oracle_id
+        JOIN (
+            SELECT COUNT(*) AS total_commander_decks
+                FROM deck
+                WHERE commander = $1
+                AND date_updated >= $2
+                AND ($3::text IS NULL OR format = $3)
+                AND ($4::int IS NULL OR archetype = $4)
+        ) AS total_commander_decks ON true
+        JOIN (
+            SELECT oracle_id, COUNT(oracle_id) as ci_quantity FROM decklist
+            LEFT JOIN deck ON decklist.deck_id = deck.id
+            WHERE color_identity = (SELECT color_identity FROM card WHERE oracle_id = $1)
+            AND date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
+            GROUP BY oracle_id
+        ) AS ci_card_quantity ON ci_card_quantity.oracle_id = card.oracle_id
+        JOIN (
+            SELECT COUNT(*) AS total_commander_decks_of_ci
+            FROM deck
+            WHERE color_identity = (SELECT color_identity FROM card WHERE oracle_id = $1)
+            AND date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
+        ) AS total_commander_decks_of_ci ON true
+        WHERE card.type_line_full NOT LIKE 'Basic Land%'
+        ORDER BY quantity DESC;",
+        oracle_id,
+        updated_since,
+        format,
+        archetype,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(cards
+        .iter()
+        .map(CommanderTopCardWithSynergy::add_synergy)
+        .collect())
+}
+
+// Suggestions for a deck that's being built, a card scores its synergy with the commander plus how often it's
+// played alongside the cards already chosen (the average share of the commander's decks with a chosen card
+// that also play it)
+async fn recommend(
+    Query(params): Query<ListParams>,
+    Query(format): Query<FormatParams>,
+    Query(wildcards): Query<WildcardParams>,
+    State(AppState { pool }): State<AppState>,
+    Json(request): Json<RecommendRequest>,
+) -> Result<Json<Page<Vec<RecommendedCard>>>, ApiError> {
+    let pagination = params.resolve(RECOMMEND_SORTS, 20)?;
+    let format = format.format()?;
+    let wildcards = wildcards.filter()?;
+    if request.cards.len() > MAX_RECOMMEND_CARDS {
+        return Err(ApiError::bad_request(format!(
+            "too many cards, at most {MAX_RECOMMEND_CARDS} can be chosen"
+        )));
+    }
+    let commander = sqlx::query!(
+        "SELECT oracle_id, color_identity FROM card WHERE oracle_id = $1 AND is_legal_commander = true",
+        request.commander
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no commander with oracle_id '{}'", request.commander)))?;
 
-    fn get_search_results(res: Response) -> SearchResults {
-        match res.slug {
-            Some(slug) => SearchResults {
-                card_name: res.name_full,
-                image: res.image_art_crop,
-                slug: get_route(res.is_legal_commander, slug),
-            },
-            None => panic!("Not found"),
+    let co_occurrences = sqlx::query!(
+        r#"WITH CommanderDecks AS (
+            SELECT id FROM deck
+            WHERE commander = $3
+            AND ($2::text IS NULL OR format = $2)
+        ),
+        Chosen AS (
+            SELECT decklist.oracle_id, COUNT(*) AS total_decks
+            FROM decklist
+            JOIN CommanderDecks ON CommanderDecks.id = decklist.deck_id
+            WHERE decklist.oracle_id = ANY($1)
+            GROUP BY decklist.oracle_id
+        )
+        SELECT
+            other.oracle_id AS "oracle_id!",
+            (SUM(1.0 / Chosen.total_decks) / (SELECT COUNT(*) FROM Chosen) * 100)::float AS "co_occurrence!"
+        FROM Chosen
+        JOIN decklist this ON this.oracle_id = Chosen.oracle_id
+        JOIN CommanderDecks ON CommanderDecks.id = this.deck_id
+        JOIN decklist other ON other.deck_id = this.deck_id AND other.oracle_id <> this.oracle_id
+        GROUP BY other.oracle_id"#,
+        &request.cards,
+        format,
+        request.commander
+    )
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.oracle_id.to_string(), row.co_occurrence))
+    .collect::<HashMap<String, f64>>();
+
+    let chosen: Vec<String> = request.cards.iter().map(Uuid::to_string).collect();
+    let mut recommendations: Vec<RecommendedCard> =
+        commander_cards_with_synergy(&pool, request.commander, 0, format, None)
+            .await?
+            .into_iter()
+            .filter(|card| !chosen.contains(&card.oracle_id))
+            .filter(|card| {
+                card.color_identity
+                    .iter()
+                    .all(|color| commander.color_identity.contains(color))
+            })
+            .map(|card| {
+                let co_occurrence = co_occurrences
+                    .get(&card.oracle_id)
+                    .copied()
+                    .unwrap_or_default();
+                RecommendedCard {
+                    score: card.synergy + co_occurrence,
+                    synergy: card.synergy,
+                    co_occurrence,
+                    usage_in_commander: card.usage_in_commander,
+                    oracle_id: card.oracle_id,
+                    name_full: card.name_full,
+                    slug: card.slug,
+                    type_line_front: card.type_line_front,
+                    mana_cost_front: card.mana_cost_front,
+                    cmc: card.cmc,
+                    color_identity: card.color_identity,
+                    lowest_rarity: card.lowest_rarity,
+                    image_small: card.image_small,
+                    image_normal: card.image_normal,
+                    image_art_crop: card.image_art_crop,
+                }
+            })
+            .collect();
+
+    recommendations.sort_by(|a, b| {
+        let ordering = match pagination.sort {
+            "synergy" => a.synergy.total_cmp(&b.synergy),
+            "co_occurrence" => a.co_occurrence.total_cmp(&b.co_occurrence),
+            "name" => a.name_full.cmp(&b.name_full),
+            _ => a.score.total_cmp(&b.score),
+        };
+        if pagination.descending {
+            ordering.reverse()
+        } else {
+            ordering
         }
+    });
+
+    let recommendations = wildcards.apply(
+        recommendations,
+        pagination.per_page as usize,
+        |card| &card.lowest_rarity,
+    );
+    let total = recommendations.len() as i64;
+    Ok(Json(Page::new(
+        pagination.slice(recommendations),
+        total,
+        &pagination,
+    )))
+}
+
+// return the front face name the of flip cards
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResults {
+    card_name: String,
+    image: String,
+    slug: String,
+    is_commander: bool,
+}
+
+// Accent-insensitive name search, ranked by prefix matches, then word prefix matches, then substring and fuzzy matches
+// Back face names are searched as well, so "insectile" finds "Delver of Secrets // Insectile Aberration"
+async fn get_card(
+    Path(card_name): Path<String>,
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Vec<SearchResults>>, ApiError> {
+    struct Response {
+        name_full: String,
+        image_art_crop: String,
+        slug: Option<String>,
+        is_legal_commander: bool,
     }
 
-    let search_results: Vec<SearchResults> = res.into_iter().map(get_search_results).collect();
-    Json(search_results)
+    // The search is used as a LIKE pattern, so wildcards typed by the user have to be escaped
+    let pattern = card_name
+        .trim()
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    let res = sqlx::query_as!(
+        Response,
+        r#"WITH Names AS (
+            SELECT
+                card.oracle_id,
+                card.name_full,
+                card.image_art_crop,
+                card.slug,
+                card.is_legal_commander,
+                names.name
+            FROM card
+            CROSS JOIN LATERAL (
+                SELECT unaccent(lower(card.name_full)) AS name
+                UNION ALL
+                SELECT unaccent(lower(card.name_back)) WHERE card.name_back IS NOT NULL
+            ) AS names
+            WHERE card.is_legal = true
+        ),
+        Matches AS (
+            SELECT
+                oracle_id,
+                name_full,
+                image_art_crop,
+                slug,
+                is_legal_commander,
+                CASE
+                    WHEN name LIKE unaccent($2) || '%' THEN 0
+                    WHEN ' ' || name LIKE '% ' || unaccent($2) || '%' THEN 1
+                    WHEN name LIKE '%' || unaccent($2) || '%' THEN 2
+                    ELSE 3
+                END AS rank,
+                word_similarity(unaccent(lower($1)), name) AS similarity,
+                name = unaccent(lower($1)) AS is_exact
+            FROM Names
+        )
+        SELECT
+            name_full AS "name_full!",
+            image_art_crop AS "image_art_crop!",
+            slug,
+            is_legal_commander AS "is_legal_commander!"
+        FROM Matches
+        WHERE rank < 3 OR similarity > 0.4::real
+        GROUP BY oracle_id, name_full, image_art_crop, slug, is_legal_commander
+        ORDER BY MIN(rank), bool_or(is_exact) DESC, MAX(similarity) DESC, is_legal_commander DESC, name_full
+        LIMIT 20"#,
+        card_name.trim(),
+        pattern
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    fn get_route(is_commander: bool, slug: String) -> String {
+        if is_commander {
+            format!("/commander/{}", slug)
+        } else {
+            format!("/card/{}", slug)
+        }
+    }
+
+    fn get_search_results(res: Response) -> Option<SearchResults> {
+        res.slug.map(|slug| SearchResults {
+            card_name: res.name_full,
+            image: res.image_art_crop,
+            slug: get_route(res.is_legal_commander, slug),
+            is_commander: res.is_legal_commander,
+        })
+    }
+
+    let search_results: Vec<SearchResults> =
+        res.into_iter().filter_map(get_search_results).collect();
+    Ok(Json(search_results))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+}
+
+// Searches legal cards with a Scryfall-like query, eg. `/search?q=t:creature o:"sacrifice a" id<=r`
+async fn search_cards(
+    Query(search): Query<SearchParams>,
+    Query(params): Query<ListParams>,
+    State(AppState { pool }): State<AppState>,
+) -> Result<Json<Page<Vec<SearchCard>>>, ApiError> {
+    let query = CardQuery::parse(search.q.as_deref().unwrap_or_default())?;
+    let pagination = params.resolve(SEARCH_SORTS, 50)?;
+
+    let mut builder = QueryBuilder::new(
+        "SELECT
+            card.oracle_id::text AS oracle_id, card.name_full, card.slug, card.mana_cost_combined,
+            card.cmc, card.type_line_full, card.oracle_text, card.color_identity, card.rarity,
+            card.is_legal_commander, card.is_rebalanced, card.image_small, card.image_normal, card.image_art_crop
+        FROM card
+        WHERE card.is_legal = true",
+    );
+    query.push_filters(&mut builder);
+    let sort = match pagination.sort {
+        "cmc" => "card.cmc",
+        _ => "card.name_full",
+    };
+    let order = if pagination.descending { "DESC" } else { "ASC" };
+    builder
+        .push(format!(" ORDER BY {sort} {order}, card.name_full LIMIT "))
+        .push_bind(pagination.per_page)
+        .push(" OFFSET ")
+        .push_bind(pagination.offset());
+    let res = builder
+        .build_query_as::<SearchCard>()
+        .fetch_all(&pool)
+        .await?;
+
+    let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM card WHERE card.is_legal = true");
+    query.push_filters(&mut builder);
+    let total: i64 = builder.build_query_scalar().fetch_one(&pool).await?;
+
+    Ok(Json(Page::new(res, total, &pagination)))
 }
 
 async fn top_commanders_for_card(
     Path(slug): Path<String>,
+    Query(format): Query<FormatParams>,
     State(AppState { pool }): State<AppState>,
-) -> Json<Vec<TopCards>> {
+) -> Result<Json<Vec<TopCards>>, ApiError> {
+    let format = format.format()?;
     let res = sqlx::query_as!(
         TopCards,
         r#"WITH CommanderDecks AS (
@@ -795,6 +1594,8 @@ async fn top_commanders_for_card(
                 COUNT(DISTINCT deck.id) AS "total_commander_decks!"
             FROM
                 deck
+            WHERE
+                ($2::text IS NULL OR deck.format = $2)
             GROUP BY
                 deck.commander
             HAVING
@@ -814,6 +1615,7 @@ async fn top_commanders_for_card(
             CommanderDecks cd
         JOIN
             deck ON cd.commander_id = deck.commander
+            AND ($2::text IS NULL OR deck.format = $2)
         JOIN
             decklist ON deck.id = decklist.deck_id
         JOIN
@@ -844,6 +1646,8 @@ async fn top_commanders_for_card(
             card.colors_back,
             card.color_identity,
             card.is_legal,
+            card.is_legal_standard,
+            card.arena_id,
             card.is_legal_commander,
             card.is_rebalanced,
             card.image_small,
@@ -862,12 +1666,903 @@ async fn top_commanders_for_card(
             "rank!" DESC;
         
         "#,
+        slug,
+        format
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Json(res))
+}
+
+// A starting decklist for a commander built from the cards most played with it
+// The land count is the median of the real decks, split between the most played nonbasic lands and basics
+async fn commander_average_deck(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(format): Query<FormatParams>,
+) -> Result<Json<AverageDeck>, ApiError> {
+    let format = format.format_or_default()?;
+    let commander = sqlx::query_as!(
+        Card,
+        "SELECT * FROM card WHERE slug = $1 AND is_legal_commander = true LIMIT 1",
         slug
     )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no commander with slug '{slug}'")))?;
+    let commander_id = Uuid::parse_str(&commander.oracle_id)?;
+
+    let average = average_decks(&pool, format, Some(commander_id), 1)
+        .await?
+        .remove(&commander.oracle_id)
+        .ok_or_else(|| ApiError::not_found(format!("no decks with '{slug}' as the commander")))?;
+
+    let mut wildcards = WildcardCost::default();
+    wildcards.add(&commander.lowest_rarity, &commander.type_line_front, 1);
+    for card in &average.cards {
+        wildcards.add(&card.lowest_rarity, &card.type_line_front, card.quantity);
+    }
+
+    Ok(Json(AverageDeck {
+        commander,
+        total_decks: average.total_decks,
+        deck_size: validation::required_deck_size(Some(format)),
+        land_count: average.land_count,
+        wildcards,
+        cards: average.cards,
+    }))
+}
+
+// Average decks by commander oracle_id, for `commander` alone or every commander with at least `min_decks` decks
+async fn average_decks(
+    pool: &Pool<Postgres>,
+    format: &str,
+    commander: Option<Uuid>,
+    min_decks: i64,
+) -> Result<HashMap<String, CommanderAverageDeck>, ApiError> {
+    let lands = sqlx::query!(
+        r#"WITH DeckLands AS (
+            SELECT
+                deck.id,
+                deck.commander,
+                COALESCE(SUM(decklist.quantity) FILTER (WHERE is_land(card.type_line_front)), 0) AS lands,
+                COALESCE(SUM(decklist.quantity) FILTER (
+                    WHERE is_land(card.type_line_front) AND card.type_line_front NOT LIKE 'Basic%'
+                ), 0) AS nonbasic_lands
+            FROM deck
+            JOIN decklist ON decklist.deck_id = deck.id
+            JOIN card ON card.oracle_id = decklist.oracle_id
+            WHERE ($1::uuid IS NULL OR deck.commander = $1)
+            AND deck.format = $2
+            GROUP BY deck.id
+        )
+        SELECT
+            commander,
+            COUNT(*) AS "total_decks!",
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY lands) AS "lands!",
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY nonbasic_lands) AS "nonbasic_lands!"
+        FROM DeckLands
+        GROUP BY commander
+        HAVING COUNT(*) >= $3"#,
+        commander,
+        format,
+        min_decks
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let commanders: Vec<Uuid> = lands.iter().map(|lands| lands.commander).collect();
+
+    // Basics carry the total number of copies played in `quantity`, which assemble splits them by
+    let cards = sqlx::query!(
+        r#"SELECT
+            deck.commander,
+            card.oracle_id, card.name_full, card.slug, card.layout, card.type_line_front,
+            card.mana_cost_front, card.cmc, card.color_identity, card.lowest_rarity, card.image_small,
+            card.image_normal, COUNT(DISTINCT deck.id) AS "decks!",
+            SUM(decklist.quantity)::int AS "quantity!",
+            card.type_line_front LIKE 'Basic Land%' AS "is_basic!"
+        FROM deck
+        JOIN decklist ON decklist.deck_id = deck.id
+        JOIN card ON card.oracle_id = decklist.oracle_id
+        JOIN card commander ON commander.oracle_id = deck.commander
+        WHERE deck.commander = ANY($1)
+        AND deck.format = $2
+        AND decklist.oracle_id <> deck.commander
+        AND NOT decklist.is_companion
+        AND card.color_identity <@ commander.color_identity
+        AND (
+            card.type_line_front LIKE 'Basic Land%'
+            OR CASE WHEN $2 = 'standard' THEN card.is_legal_standard ELSE card.is_legal END
+        )
+        GROUP BY deck.commander, card.oracle_id
+        ORDER BY deck.commander, "decks!" DESC, card.name_full"#,
+        &commanders,
+        format
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut candidates: HashMap<Uuid, (Vec<AverageDeckCard>, Vec<AverageDeckCard>)> =
+        HashMap::new();
+    for card in cards {
+        let (nonbasics, basics) = candidates.entry(card.commander).or_default();
+        let is_basic = card.is_basic;
+        let card = AverageDeckCard {
+            oracle_id: card.oracle_id.to_string(),
+            name_full: card.name_full,
+            slug: card.slug,
+            layout: card.layout,
+            type_line_front: card.type_line_front,
+            mana_cost_front: card.mana_cost_front,
+            cmc: card.cmc,
+            color_identity: card.color_identity,
+            lowest_rarity: card.lowest_rarity,
+            image_small: card.image_small,
+            image_normal: card.image_normal,
+            decks: card.decks,
+            quantity: if is_basic { card.quantity } else { 1 },
+        };
+        if is_basic {
+            basics.push(card);
+        } else {
+            nonbasics.push(card);
+        }
+    }
+
+    let deck_size = validation::required_deck_size(Some(format));
+    Ok(lands
+        .into_iter()
+        .map(|lands| {
+            let (nonbasics, basics) = candidates.remove(&lands.commander).unwrap_or_default();
+            let land_count = lands.lands.round() as i32;
+            let cards = average_deck::assemble(
+                nonbasics,
+                basics,
+                deck_size,
+                land_count,
+                lands.nonbasic_lands.round() as i32,
+            );
+            (
+                lands.commander.to_string(),
+                CommanderAverageDeck {
+                    total_decks: lands.total_decks,
+                    land_count,
+                    cards,
+                },
+            )
+        })
+        .collect())
+}
+
+// Stores an Arena collection exported by a tracker (CSV or JSON of arena_id and count)
+// The returned id is passed back as `?collection=` and stands in for the user's session
+async fn import_collection(
+    State(AppState { pool }): State<AppState>,
+    body: String,
+) -> Result<Json<CollectionImport>, ApiError> {
+    let counts = collection::parse_collection(&body)?;
+    let arena_ids: Vec<i32> = counts.iter().map(|(arena_id, _)| *arena_id).collect();
+
+    // Older printings have their own arena_id, which only the printing table knows about
+    let printings: HashMap<i32, String> = sqlx::query!(
+        r#"SELECT arena_id AS "arena_id!", oracle_id AS "oracle_id!" FROM card WHERE arena_id = ANY($1)
+        UNION
+        SELECT arena_id AS "arena_id!", oracle_id AS "oracle_id!" FROM printing WHERE arena_id = ANY($1)"#,
+        &arena_ids
+    )
     .fetch_all(&pool)
-    .await
-    .expect("couldn't fetch commanders");
-    Json(res)
+    .await?
+    .into_iter()
+    .map(|printing| (printing.arena_id, printing.oracle_id.to_string()))
+    .collect();
+
+    let mut owned: HashMap<String, i32> = HashMap::new();
+    let mut unresolved = vec![];
+    for (arena_id, count) in counts {
+        match printings.get(&arena_id) {
+            Some(oracle_id) => *owned.entry(oracle_id.clone()).or_default() += count,
+            None => unresolved.push(arena_id),
+        }
+    }
+    let (oracle_ids, quantities): (Vec<Uuid>, Vec<i32>) = owned
+        .into_iter()
+        .map(|(oracle_id, quantity)| Ok((Uuid::parse_str(&oracle_id)?, quantity)))
+        .collect::<Result<Vec<(Uuid, i32)>, ApiError>>()?
+        .into_iter()
+        .unzip();
+
+    let mut transaction = pool.begin().await?;
+    let id = sqlx::query_scalar!(
+        "INSERT INTO collection (date_created) VALUES ($1) RETURNING id",
+        chrono::Utc::now().timestamp_millis()
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO collection_card (collection_id, oracle_id, quantity)
+        SELECT $1, * FROM UNNEST($2::uuid[], $3::int[])",
+        id,
+        &oracle_ids,
+        &quantities
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    Ok(Json(CollectionImport {
+        id: id.to_string(),
+        cards: oracle_ids.len() as i64,
+        unresolved,
+    }))
+}
+
+// Copies owned by oracle_id
+async fn owned_cards(
+    pool: &Pool<Postgres>,
+    collection: Uuid,
+) -> Result<HashMap<String, i32>, ApiError> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM collection WHERE id = $1) AS "exists!""#,
+        collection
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Err(ApiError::not_found(format!("no collection with id {collection}")));
+    }
+
+    Ok(sqlx::query!(
+        "SELECT oracle_id, quantity FROM collection_card WHERE collection_id = $1",
+        collection
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|card| (card.oracle_id.to_string(), card.quantity))
+    .collect())
+}
+
+// Commanders ranked by how much of their average deck the collection already has
+// Basic lands are free on Arena so they always count as owned, the rest of the deck is what's left to craft
+async fn collection_commanders(
+    State(AppState { pool }): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Query(params): Query<ListParams>,
+    Query(format): Query<FormatParams>,
+) -> Result<Json<Page<Vec<CommanderOwnership>>>, ApiError> {
+    let pagination = params.resolve(COLLECTION_COMMANDER_SORTS, 20)?;
+    let format = format.format_or_default()?;
+    let owned = owned_cards(&pool, collection_id).await?;
+    let average_decks = average_decks(&pool, format, None, 5).await?;
+
+    let commander_ids = average_decks
+        .keys()
+        .map(|oracle_id| Uuid::parse_str(oracle_id))
+        .collect::<Result<Vec<Uuid>, uuid::Error>>()?;
+    let commanders = sqlx::query!(
+        "SELECT oracle_id, name_full, slug, type_line_front, color_identity, lowest_rarity,
+            image_small, image_normal, image_art_crop
+        FROM card
+        WHERE oracle_id = ANY($1)
+        AND ($2::text IS NULL OR $2 <> 'standard' OR is_legal_standard)",
+        &commander_ids,
+        format
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut ranking: Vec<CommanderOwnership> = commanders
+        .into_iter()
+        .filter_map(|commander| {
+            let oracle_id = commander.oracle_id.to_string();
+            let average = average_decks.get(&oracle_id)?;
+
+            let mut deck_size = 1;
+            let mut owned_cards = owned.get(&oracle_id).is_some_and(|quantity| *quantity > 0) as i32;
+            let mut missing_wildcards = WildcardCost::default();
+            missing_wildcards.add(
+                &commander.lowest_rarity,
+                &commander.type_line_front,
+                1 - owned_cards,
+            );
+            for card in &average.cards {
+                let have = if card.type_line_front.starts_with("Basic Land") {
+                    card.quantity
+                } else {
+                    owned
+                        .get(&card.oracle_id)
+                        .copied()
+                        .unwrap_or_default()
+                        .min(card.quantity)
+                };
+                deck_size += card.quantity;
+                owned_cards += have;
+                missing_wildcards.add(
+                    &card.lowest_rarity,
+                    &card.type_line_front,
+                    card.quantity - have,
+                );
+            }
+
+            Some(CommanderOwnership {
+                oracle_id,
+                name_full: commander.name_full,
+                slug: commander.slug,
+                color_identity: commander.color_identity,
+                image_small: commander.image_small,
+                image_normal: commander.image_normal,
+                image_art_crop: commander.image_art_crop,
+                total_decks: average.total_decks,
+                deck_size,
+                owned_cards,
+                owned_percent: owned_cards as f64 * 100.0 / deck_size as f64,
+                missing_wildcards,
+            })
+        })
+        .collect();
+
+    ranking.sort_by(|a, b| {
+        let ordering = match pagination.sort {
+            "decks" => a.total_decks.cmp(&b.total_decks),
+            "name" => a.name_full.cmp(&b.name_full),
+            _ => a
+                .owned_percent
+                .total_cmp(&b.owned_percent)
+                .then(a.total_decks.cmp(&b.total_decks)),
+        };
+        if pagination.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    let total = ranking.len() as i64;
+    Ok(Json(Page::new(pagination.slice(ranking), total, &pagination)))
+}
+
+// Every printing of a card, newest first
+async fn card_printings(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Json<Vec<Printing>>, ApiError> {
+    let oracle_id = sqlx::query_scalar!("SELECT oracle_id FROM card WHERE slug = $1 LIMIT 1", slug)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("no card with slug '{slug}'")))?;
+
+    let res = sqlx::query_as!(
+        Printing,
+        r#"SELECT
+            scryfall_id, arena_id, set_code, set_name, collector_number,
+            released_at::text AS "released_at!", rarity, lang
+        FROM printing
+        WHERE oracle_id = $1
+        ORDER BY printing.released_at DESC, set_code, collector_number"#,
+        oracle_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(res))
+}
+
+// Cards most often found in the same decks as this card
+// lift is how much more likely a deck with this card is to play the other card than decks in general, 1.0 means no difference
+async fn card_pairs(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Page<Vec<CardPair>>>, ApiError> {
+    let pagination = params.resolve(CARD_PAIR_SORTS, 50)?;
+    let oracle_id = sqlx::query_scalar!("SELECT oracle_id FROM card WHERE slug = $1 LIMIT 1", slug)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("no card with slug '{slug}'")))?;
+
+    // Every count is taken from decklist in the same query, total_decks_per_card is only refreshed by the
+    // migration tool and would be behind decks synced since
+    let res = sqlx::query_as!(
+        CardPair,
+        r#"WITH ThisDecks AS (
+            SELECT deck_id FROM decklist WHERE oracle_id = $1
+        ),
+        Pairs AS (
+            SELECT other.oracle_id, COUNT(*) AS decks_together
+            FROM ThisDecks
+            JOIN decklist other ON other.deck_id = ThisDecks.deck_id AND other.oracle_id <> $1
+            GROUP BY other.oracle_id
+        ),
+        DecksPerCard AS (
+            SELECT oracle_id, COUNT(*)::int AS total_decks
+            FROM decklist
+            WHERE oracle_id IN (SELECT oracle_id FROM Pairs)
+            GROUP BY oracle_id
+        ),
+        AllDecks AS (
+            SELECT COUNT(*) AS total FROM deck
+        ),
+        Lifts AS (
+            SELECT
+                Pairs.oracle_id,
+                Pairs.decks_together,
+                DecksPerCard.total_decks,
+                Pairs.decks_together::float / (SELECT COUNT(*) FROM ThisDecks)
+                    / (DecksPerCard.total_decks::float / AllDecks.total) AS lift
+            FROM Pairs
+            JOIN DecksPerCard ON DecksPerCard.oracle_id = Pairs.oracle_id
+            JOIN AllDecks ON true
+        )
+        SELECT
+            card.oracle_id, card.name_full, card.slug, card.type_line_front, card.cmc, card.color_identity,
+            card.image_small, card.image_normal, card.image_art_crop, card.is_legal_commander,
+            Lifts.decks_together AS "decks_together!",
+            Lifts.total_decks AS "decks_with_card!",
+            Lifts.lift AS "lift!"
+        FROM Lifts
+        JOIN card ON card.oracle_id = Lifts.oracle_id
+        WHERE card.type_line_front NOT LIKE 'Basic Land%'
+        ORDER BY
+            CASE WHEN $2 = 'decks' AND $3 THEN Lifts.decks_together END DESC,
+            CASE WHEN $2 = 'decks' AND NOT $3 THEN Lifts.decks_together END ASC,
+            CASE WHEN $2 = 'lift' AND $3 THEN Lifts.lift END DESC,
+            CASE WHEN $2 = 'lift' AND NOT $3 THEN Lifts.lift END ASC,
+            CASE WHEN $2 = 'name' AND $3 THEN card.name_full END DESC,
+            CASE WHEN $2 = 'name' AND NOT $3 THEN card.name_full END ASC,
+            Lifts.decks_together DESC,
+            card.name_full
+        LIMIT $4 OFFSET $5"#,
+        oracle_id,
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset()
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(DISTINCT other.oracle_id) AS "total!"
+        FROM decklist this
+        JOIN decklist other ON other.deck_id = this.deck_id AND other.oracle_id <> this.oracle_id
+        JOIN card ON card.oracle_id = other.oracle_id
+        WHERE this.oracle_id = $1
+        AND card.type_line_front NOT LIKE 'Basic Land%'"#,
+        oracle_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page::new(res, total, &pagination)))
+}
+
+// Commanders whose decks play the same cards, from commander_similarity which the migration tool refreshes
+async fn similar_commanders(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Page<Vec<SimilarCommander>>>, ApiError> {
+    let pagination = params.resolve(SIMILAR_COMMANDER_SORTS, 20)?;
+    let commander = sqlx::query_scalar!(
+        "SELECT oracle_id FROM card WHERE slug = $1 AND is_legal_commander = true LIMIT 1",
+        slug
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no commander with slug '{slug}'")))?;
+
+    let res = sqlx::query_as!(
+        SimilarCommander,
+        r#"SELECT
+            card.oracle_id, card.name_full, card.slug, card.type_line_front, card.cmc, card.color_identity,
+            card.image_small, card.image_normal, card.image_art_crop,
+            commander_similarity.similarity,
+            commander_similarity.shared_cards
+        FROM commander_similarity
+        JOIN card ON card.oracle_id = commander_similarity.similar_commander
+        WHERE commander_similarity.commander = $1
+        ORDER BY
+            CASE WHEN $2 = 'similarity' AND $3 THEN commander_similarity.similarity END DESC,
+            CASE WHEN $2 = 'similarity' AND NOT $3 THEN commander_similarity.similarity END ASC,
+            CASE WHEN $2 = 'shared' AND $3 THEN commander_similarity.shared_cards END DESC,
+            CASE WHEN $2 = 'shared' AND NOT $3 THEN commander_similarity.shared_cards END ASC,
+            CASE WHEN $2 = 'name' AND $3 THEN card.name_full END DESC,
+            CASE WHEN $2 = 'name' AND NOT $3 THEN card.name_full END ASC,
+            commander_similarity.similarity DESC,
+            card.name_full
+        LIMIT $4 OFFSET $5"#,
+        commander,
+        pagination.sort,
+        pagination.descending,
+        pagination.per_page,
+        pagination.offset()
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "total!" FROM commander_similarity WHERE commander = $1"#,
+        commander
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page::new(res, total, &pagination)))
+}
+
+// Weekly popularity of a commander or card, bucketed by deck.date_created so editing a deck doesn't move it
+// to the latest week, the time window also applies to date_created. Weeks without any deck are returned with
+// zero decks, from the first week with a deck to the current one
+async fn commander_trend(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+) -> Result<Json<Vec<TrendBucket>>, ApiError> {
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+    let commander = sqlx::query_scalar!(
+        "SELECT oracle_id FROM card WHERE slug = $1 AND is_legal_commander = true LIMIT 1",
+        slug
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no commander with slug '{slug}'")))?;
+
+    // inclusion_rate is the share of all decks that week helmed by this commander
+    let res = sqlx::query_as!(
+        TrendBucket,
+        r#"WITH WeeklyDecks AS (
+            SELECT
+                date_trunc('week', to_timestamp(date_created / 1000.0) AT TIME ZONE 'UTC') AS week,
+                COUNT(*) FILTER (WHERE commander = $1) AS decks,
+                COUNT(*) AS total_decks
+            FROM deck
+            WHERE date_created >= $2
+            AND ($3::text IS NULL OR format = $3)
+            GROUP BY week
+        ),
+        Weeks AS (
+            SELECT generate_series(
+                (SELECT MIN(week) FROM WeeklyDecks),
+                (SELECT GREATEST(MAX(week), date_trunc('week', now() AT TIME ZONE 'UTC')) FROM WeeklyDecks),
+                interval '1 week'
+            ) AS week
+        )
+        SELECT
+            (EXTRACT(EPOCH FROM Weeks.week) * 1000)::bigint AS "week_start!",
+            COALESCE(decks, 0) AS "decks!",
+            COALESCE(total_decks, 0) AS "total_decks!",
+            COALESCE(decks * 100.0 / NULLIF(total_decks, 0), 0)::float AS "inclusion_rate!"
+        FROM Weeks
+        LEFT JOIN WeeklyDecks ON WeeklyDecks.week = Weeks.week
+        ORDER BY Weeks.week;"#,
+        commander,
+        updated_since,
+        format
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(res))
+}
+
+// How decks of the commander are built, the commander and companion aren't counted as part of the deck
+async fn commander_profile(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+    Query(archetype): Query<ArchetypeParams>,
+) -> Result<Json<CommanderProfile>, ApiError> {
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+    let archetype = archetype.archetype()?;
+    let commander = sqlx::query_scalar!(
+        "SELECT oracle_id FROM card WHERE slug = $1 AND is_legal_commander = true LIMIT 1",
+        slug
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no commander with slug '{slug}'")))?;
+
+    // Types are counted the same way deck_by_id sorts its decklist, a card only counts towards its first type
+    // median_lands counts every card with the Land type, like the average deck does
+    let profile = sqlx::query!(
+        r#"WITH CommanderDecks AS (
+            SELECT id, companion FROM deck
+            WHERE commander = $1
+            AND date_updated >= $2
+            AND ($3::text IS NULL OR format = $3)
+            AND ($4::int IS NULL OR archetype = $4)
+        ),
+        DeckCards AS (
+            SELECT
+                CommanderDecks.id,
+                decklist.quantity,
+                card.cmc,
+                is_land(card.type_line_front) AS is_land,
+                CASE
+                    WHEN card.type_line_front ILIKE '%creature%' THEN 'creature'
+                    WHEN card.type_line_front ILIKE '%instant%' THEN 'instant'
+                    WHEN card.type_line_front ILIKE '%sorcery%' THEN 'sorcery'
+                    WHEN card.type_line_front ILIKE '%planeswalker%' THEN 'planeswalker'
+                    WHEN card.type_line_front ILIKE '%enchantment%' THEN 'enchantment'
+                    WHEN is_land(card.type_line_front) THEN 'land'
+                    WHEN card.type_line_front ILIKE '%artifact%' THEN 'artifact'
+                    ELSE 'other'
+                END AS card_type
+            FROM CommanderDecks
+            JOIN decklist ON decklist.deck_id = CommanderDecks.id
+            JOIN card ON card.oracle_id = decklist.oracle_id
+            WHERE NOT decklist.is_commander
+            AND NOT decklist.is_companion
+        ),
+        DeckStats AS (
+            SELECT
+                id,
+                COALESCE(SUM(quantity) FILTER (WHERE is_land), 0) AS lands,
+                SUM(cmc * quantity) FILTER (WHERE NOT is_land)
+                    / NULLIF(SUM(quantity) FILTER (WHERE NOT is_land), 0) AS average_cmc,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'creature'), 0) AS creatures,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'instant'), 0) AS instants,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'sorcery'), 0) AS sorceries,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'planeswalker'), 0) AS planeswalkers,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'enchantment'), 0) AS enchantments,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'land'), 0) AS type_lands,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'artifact'), 0) AS artifacts,
+                COALESCE(SUM(quantity) FILTER (WHERE card_type = 'other'), 0) AS other
+            FROM DeckCards
+            GROUP BY id
+        )
+        SELECT
+            (SELECT COUNT(*) FROM CommanderDecks) AS "decks!",
+            COALESCE((SELECT COUNT(*) FILTER (WHERE companion IS NOT NULL) * 100.0 / NULLIF(COUNT(*), 0)
+                FROM CommanderDecks), 0)::float AS "companion_percent!",
+            COALESCE(percentile_cont(0.5) WITHIN GROUP (ORDER BY lands), 0) AS "median_lands!",
+            COALESCE(AVG(average_cmc), 0)::float AS "average_cmc!",
+            COALESCE(AVG(creatures), 0)::float AS "creatures!",
+            COALESCE(AVG(instants), 0)::float AS "instants!",
+            COALESCE(AVG(sorceries), 0)::float AS "sorceries!",
+            COALESCE(AVG(artifacts), 0)::float AS "artifacts!",
+            COALESCE(AVG(enchantments), 0)::float AS "enchantments!",
+            COALESCE(AVG(planeswalkers), 0)::float AS "planeswalkers!",
+            COALESCE(AVG(type_lands), 0)::float AS "lands!",
+            COALESCE(AVG(other), 0)::float AS "other!"
+        FROM DeckStats"#,
+        commander,
+        updated_since,
+        format,
+        archetype
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    let most_common_companion = sqlx::query_as!(
+        ProfileCompanion,
+        r#"SELECT card.oracle_id::text AS "oracle_id!", card.name_full, card.slug, COUNT(*) AS "decks!"
+        FROM deck
+        JOIN card ON card.oracle_id = deck.companion
+        WHERE deck.commander = $1
+        AND deck.date_updated >= $2
+        AND ($3::text IS NULL OR deck.format = $3)
+        AND ($4::int IS NULL OR deck.archetype = $4)
+        GROUP BY card.oracle_id, card.name_full, card.slug
+        ORDER BY COUNT(*) DESC, card.name_full
+        LIMIT 1"#,
+        commander,
+        updated_since,
+        format,
+        archetype
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(Json(CommanderProfile {
+        decks: profile.decks,
+        median_lands: profile.median_lands,
+        average_cmc: profile.average_cmc,
+        types: TypeMix {
+            creatures: profile.creatures,
+            instants: profile.instants,
+            sorceries: profile.sorceries,
+            artifacts: profile.artifacts,
+            enchantments: profile.enchantments,
+            planeswalkers: profile.planeswalkers,
+            lands: profile.lands,
+            other: profile.other,
+        },
+        companion_percent: profile.companion_percent,
+        most_common_companion,
+    }))
+}
+
+async fn card_trend(
+    State(AppState { pool }): State<AppState>,
+    Path(slug): Path<String>,
+    Query(time_window): Query<TimeWindowParams>,
+    Query(format): Query<FormatParams>,
+) -> Result<Json<Vec<TrendBucket>>, ApiError> {
+    let updated_since = time_window.updated_since()?;
+    let format = format.format()?;
+    let card = sqlx::query!(
+        "SELECT oracle_id, color_identity FROM card WHERE slug = $1 LIMIT 1",
+        slug
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| ApiError::not_found(format!("no card with slug '{slug}'")))?;
+
+    // inclusion_rate is the share of decks that could play the card that week which do play it
+    let res = sqlx::query_as!(
+        TrendBucket,
+        r#"WITH WeeklyDecks AS (
+            SELECT
+                date_trunc('week', to_timestamp(deck.date_created / 1000.0) AT TIME ZONE 'UTC') AS week,
+                COUNT(decklist.deck_id) AS decks,
+                COUNT(*) AS total_decks
+            FROM deck
+            LEFT JOIN decklist ON decklist.deck_id = deck.id AND decklist.oracle_id = $1
+            WHERE deck.color_identity @> $2::char(1)[]
+            AND deck.date_created >= $3
+            AND ($4::text IS NULL OR deck.format = $4)
+            GROUP BY week
+        ),
+        Weeks AS (
+            SELECT generate_series(
+                (SELECT MIN(week) FROM WeeklyDecks),
+                (SELECT GREATEST(MAX(week), date_trunc('week', now() AT TIME ZONE 'UTC')) FROM WeeklyDecks),
+                interval '1 week'
+            ) AS week
+        )
+        SELECT
+            (EXTRACT(EPOCH FROM Weeks.week) * 1000)::bigint AS "week_start!",
+            COALESCE(decks, 0) AS "decks!",
+            COALESCE(total_decks, 0) AS "total_decks!",
+            COALESCE(decks * 100.0 / NULLIF(total_decks, 0), 0)::float AS "inclusion_rate!"
+        FROM Weeks
+        LEFT JOIN WeeklyDecks ON WeeklyDecks.week = Weeks.week
+        ORDER BY Weeks.week;"#,
+        card.oracle_id,
+        &card.color_identity,
+        updated_since,
+        format
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(res))
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Printing {
+    scryfall_id: String,
+    arena_id: Option<i32>,
+    set_code: String,
+    set_name: String,
+    collector_number: String,
+    released_at: String,
+    rarity: String,
+    lang: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CollectionImport {
+    id: String,
+    cards: i64,
+    // Arena ids that didn't match a card, eg. cards that aren't legal in Brawl
+    unresolved: Vec<i32>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CommanderOwnership {
+    oracle_id: String,
+    name_full: String,
+    slug: String,
+    color_identity: Vec<String>,
+    image_small: String,
+    image_normal: String,
+    image_art_crop: String,
+    total_decks: i64,
+    deck_size: i32,
+    owned_cards: i32,
+    owned_percent: f64,
+    missing_wildcards: WildcardCost,
+}
+
+// Body of POST /recommend, `cards` are the oracle ids already in the deck
+#[derive(Debug, Deserialize)]
+struct RecommendRequest {
+    commander: Uuid,
+    #[serde(default)]
+    cards: Vec<Uuid>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RecommendedCard {
+    oracle_id: String,
+    name_full: String,
+    slug: String,
+    type_line_front: String,
+    mana_cost_front: Option<String>,
+    cmc: f32,
+    color_identity: Vec<String>,
+    lowest_rarity: String,
+    image_small: String,
+    image_normal: String,
+    image_art_crop: String,
+    usage_in_commander: f64,
+    synergy: f64,
+    co_occurrence: f64,
+    score: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SimilarCommander {
+    oracle_id: String,
+    name_full: String,
+    slug: String,
+    type_line_front: String,
+    cmc: f32,
+    color_identity: Vec<String>,
+    image_small: String,
+    image_normal: String,
+    image_art_crop: String,
+    similarity: f64,
+    shared_cards: i32,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CardPair {
+    oracle_id: String,
+    name_full: String,
+    slug: String,
+    type_line_front: String,
+    cmc: f32,
+    color_identity: Vec<String>,
+    image_small: String,
+    image_normal: String,
+    image_art_crop: String,
+    is_legal_commander: bool,
+    decks_together: i64,
+    decks_with_card: i32,
+    lift: f64,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+struct SearchCard {
+    oracle_id: String,
+    name_full: String,
+    slug: String,
+    mana_cost_combined: Option<String>,
+    cmc: f32,
+    type_line_full: String,
+    oracle_text: Option<String>,
+    color_identity: Vec<String>,
+    rarity: String,
+    is_legal_commander: bool,
+    is_rebalanced: bool,
+    image_small: String,
+    image_normal: String,
+    image_art_crop: String,
+}
+
+#[derive(Debug)]
+struct CommanderAverageDeck {
+    total_decks: i64,
+    land_count: i32,
+    cards: Vec<AverageDeckCard>,
+}
+
+#[derive(serde::Serialize)]
+struct AverageDeck {
+    commander: Card,
+    total_decks: i64,
+    deck_size: i32,
+    land_count: i32,
+    wildcards: WildcardCost,
+    cards: Vec<AverageDeckCard>,
 }
 
 #[derive(serde::Serialize)]
@@ -880,10 +2575,48 @@ struct Deck {
     commander: Card,
     companion: Option<Card>,
     color_identity: Vec<String>,
+    format: String,
+    wildcards: WildcardCost,
+    analysis: DeckAnalysis,
+    commander_baseline: CommanderBaseline,
     decklist: Decklist,
 }
 
-#[derive(serde::Serialize, Debug, Deserialize)]
+// The average analysis of every deck with the same commander and format, to compare a deck against
+#[derive(serde::Serialize)]
+struct CommanderBaseline {
+    decks: i64,
+    analysis: DeckAnalysis,
+}
+
+struct ResolvedCard {
+    card: Card,
+    quantity: i32,
+    is_commander: bool,
+    is_companion: bool,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct ParsedDeck {
+    commander: Option<ParsedCard>,
+    companion: Option<ParsedCard>,
+    color_identity: Vec<String>,
+    cards: Vec<ParsedCard>,
+    unresolved: Vec<String>,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+struct ParsedCard {
+    oracle_id: String,
+    name: String,
+    slug: String,
+    quantity: i32,
+    color_identity: Vec<String>,
+    is_commander: bool,
+    is_companion: bool,
+}
+
+#[derive(serde::Serialize, Debug, Clone, Deserialize)]
 struct Card {
     oracle_id: String,
     name_full: String,
@@ -908,6 +2641,8 @@ struct Card {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -947,6 +2682,8 @@ struct CardCount {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -987,6 +2724,8 @@ struct CardBySlug {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1003,6 +2742,23 @@ struct CardBySlug {
     total_decks_could_play: Option<i64>,
 }
 
+// A group of a commander's decks built the same way, labelled with the cards that set it apart
+#[derive(Debug, Clone, serde::Serialize)]
+struct Archetype {
+    archetype: i32,
+    label: String,
+    decks: i32,
+}
+
+// With `?archetype=N` the deck counts only include decks of that archetype
+#[derive(serde::Serialize)]
+struct CommanderSlug {
+    #[serde(flatten)]
+    card: CardSlug,
+    archetype: Option<Archetype>,
+    archetypes: Vec<Archetype>,
+}
+
 #[derive(serde::Serialize)]
 struct CardSlug {
     oracle_id: String,
@@ -1028,6 +2784,8 @@ struct CardSlug {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1071,6 +2829,8 @@ struct TopCards {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1113,6 +2873,8 @@ struct CommanderTopCard {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1156,6 +2918,8 @@ struct CommanderTopCardWithSynergy {
     colors_back: Option<Vec<String>>,
     color_identity: Vec<String>,
     is_legal: bool,
+    is_legal_standard: bool,
+    arena_id: Option<i32>,
     is_legal_commander: bool,
     is_rebalanced: bool,
     image_small: String,
@@ -1175,9 +2939,27 @@ struct CommanderTopCardWithSynergy {
     synergy: f64,
     usage_in_commander: f64,
     usage_in_color: f64,
+    // Copies in the collection given with `?collection=`
+    owned: Option<i32>,
 }
 
 impl CommanderTopCardWithSynergy {
+    fn sort(cards: &mut [CommanderTopCardWithSynergy], pagination: &Pagination) {
+        cards.sort_by(|a, b| {
+            let ordering = match pagination.sort {
+                "synergy" => a.synergy.total_cmp(&b.synergy),
+                "name" => a.name_full.cmp(&b.name_full),
+                "cmc" => a.cmc.total_cmp(&b.cmc),
+                _ => a.quantity.cmp(&b.quantity),
+            };
+            if pagination.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
     fn add_synergy(other: &CommanderTopCard) -> Self {
         let usage_in_commander =
             (other.quantity.unwrap() as f64 / other.total_commander_decks.unwrap() as f64) * 100.00;
@@ -1194,9 +2976,11 @@ impl CommanderTopCardWithSynergy {
             colors: other.colors.clone(),
             color_identity: other.color_identity.clone(),
             is_legal: other.is_legal,
+            is_legal_standard: other.is_legal_standard,
+            arena_id: other.arena_id,
             is_legal_commander: other.is_legal_commander,
             rarity: other.rarity.clone(),
-            lowest_rarity: other.rarity.clone(),
+            lowest_rarity: other.lowest_rarity.clone(),
             image_small: other.image_small.clone(),
             image_normal: other.image_normal.clone(),
             image_large: other.image_large.clone(),
@@ -1221,12 +3005,13 @@ impl CommanderTopCardWithSynergy {
             type_line_back: other.type_line_back.clone(),
             oracle_text_back: other.oracle_text_back.clone(),
             colors_back: other.colors_back.clone(),
-            is_rebalanced: other.is_rebalanced.clone(),
+            is_rebalanced: other.is_rebalanced,
             image_small_back: other.image_small_back.clone(),
             image_normal_back: other.image_normal_back.clone(),
             image_large_back: other.image_large_back.clone(),
             image_art_crop_back: other.image_art_crop_back.clone(),
             image_border_crop_back: other.image_border_crop_back.clone(),
+            owned: None,
         }
     }
 }
@@ -1243,6 +3028,93 @@ struct TopCardsForCommander {
     lands: Vec<CommanderTopCardWithSynergy>,
 }
 
+impl TopCardsForCommander {
+    // The `total` of the page envelope, see commander_top_cards
+    fn largest_bucket(&self) -> usize {
+        [
+            self.creatures.len(),
+            self.instants.len(),
+            self.sorceries.len(),
+            self.utility_artifacts.len(),
+            self.enchantments.len(),
+            self.planeswalkers.len(),
+            self.mana_artifacts.len(),
+            self.lands.len(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    }
+
+    fn filter_wildcards(self, wildcards: &WildcardFilter, per_page: usize) -> Self {
+        let apply = |cards: Vec<CommanderTopCardWithSynergy>| {
+            wildcards.apply(cards, per_page, |card| &card.lowest_rarity)
+        };
+        TopCardsForCommander {
+            creatures: apply(self.creatures),
+            instants: apply(self.instants),
+            sorceries: apply(self.sorceries),
+            utility_artifacts: apply(self.utility_artifacts),
+            enchantments: apply(self.enchantments),
+            planeswalkers: apply(self.planeswalkers),
+            mana_artifacts: apply(self.mana_artifacts),
+            lands: apply(self.lands),
+        }
+    }
+
+    fn paginate(self, pagination: &Pagination) -> Self {
+        TopCardsForCommander {
+            creatures: pagination.slice(self.creatures),
+            instants: pagination.slice(self.instants),
+            sorceries: pagination.slice(self.sorceries),
+            utility_artifacts: pagination.slice(self.utility_artifacts),
+            enchantments: pagination.slice(self.enchantments),
+            planeswalkers: pagination.slice(self.planeswalkers),
+            mana_artifacts: pagination.slice(self.mana_artifacts),
+            lands: pagination.slice(self.lands),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CommanderProfile {
+    decks: i64,
+    median_lands: f64,
+    average_cmc: f64,
+    types: TypeMix,
+    companion_percent: f64,
+    most_common_companion: Option<ProfileCompanion>,
+}
+
+// Average number of cards of each type per deck
+#[derive(Debug, serde::Serialize)]
+struct TypeMix {
+    creatures: f64,
+    instants: f64,
+    sorceries: f64,
+    artifacts: f64,
+    enchantments: f64,
+    planeswalkers: f64,
+    lands: f64,
+    other: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ProfileCompanion {
+    oracle_id: String,
+    name_full: String,
+    slug: Option<String>,
+    decks: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct TrendBucket {
+    week_start: i64,
+    decks: i64,
+    total_decks: i64,
+    inclusion_rate: f64,
+}
+
 #[derive(Debug, serde::Serialize)]
 struct Decklist {
     creatures: Vec<CardCount>,
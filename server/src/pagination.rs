@@ -0,0 +1,129 @@
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+pub const MAX_PER_PAGE: i64 = 1000;
+
+// Query parameters shared by every list endpoint, eg. `?page=2&per_page=50&sort=name&order=asc`
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+}
+
+// ListParams checked against what a specific endpoint supports
+#[derive(Debug, Clone)]
+pub struct Pagination {
+    pub page: i64,
+    pub per_page: i64,
+    pub sort: &'static str,
+    pub descending: bool,
+}
+
+impl ListParams {
+    // `sorts` is the list of sort keys the endpoint supports, the first one is the default
+    pub fn resolve(
+        &self,
+        sorts: &[&'static str],
+        default_per_page: i64,
+    ) -> Result<Pagination, ApiError> {
+        let sort = match &self.sort {
+            None => sorts[0],
+            Some(sort) => sorts
+                .iter()
+                .find(|key| key.eq_ignore_ascii_case(sort))
+                .copied()
+                .ok_or_else(|| {
+                    ApiError::bad_request(format!(
+                        "invalid sort '{sort}', expected one of: {}",
+                        sorts.join(", ")
+                    ))
+                })?,
+        };
+
+        // Names read naturally A-Z, everything else is a count where the biggest comes first
+        let descending = match self.order.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None => sort != "name",
+            Some("desc") => true,
+            Some("asc") => false,
+            Some(order) => {
+                return Err(ApiError::bad_request(format!(
+                    "invalid order '{order}', expected 'asc' or 'desc'"
+                )))
+            }
+        };
+
+        let page = self.page.unwrap_or(1);
+        if page < 1 {
+            return Err(ApiError::bad_request("page must be 1 or greater"));
+        }
+
+        let per_page = self.per_page.unwrap_or(default_per_page);
+        if !(1..=MAX_PER_PAGE).contains(&per_page) {
+            return Err(ApiError::bad_request(format!(
+                "per_page must be between 1 and {MAX_PER_PAGE}"
+            )));
+        }
+
+        // The offset has to fit in an i64 for Postgres, and leave room for `offset + per_page`
+        if (page - 1)
+            .checked_mul(per_page)
+            .and_then(|offset| offset.checked_add(per_page))
+            .is_none()
+        {
+            return Err(ApiError::bad_request("page is too large"));
+        }
+
+        Ok(Pagination {
+            page,
+            per_page,
+            sort,
+            descending,
+        })
+    }
+}
+
+impl Pagination {
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.per_page
+    }
+
+    // Slices a page out of a list that was already sorted in memory
+    pub fn slice<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(usize::try_from(self.offset()).unwrap_or(usize::MAX))
+            .take(self.per_page as usize)
+            .collect()
+    }
+
+    pub fn has_next_page(&self, total: i64) -> bool {
+        self.offset() + self.per_page < total
+    }
+}
+
+// Response envelope for every list endpoint
+#[derive(Debug, serde::Serialize)]
+pub struct Page<T> {
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub next_page: Option<i64>,
+    pub items: T,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: T, total: i64, pagination: &Pagination) -> Self {
+        Page {
+            total,
+            page: pagination.page,
+            per_page: pagination.per_page,
+            next_page: pagination
+                .has_next_page(total)
+                .then_some(pagination.page + 1),
+            items,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+// Query parameters limiting statistics to recently updated decks, eg. `?window=30d` or `?since=2024-06-01`
+#[derive(Debug, Deserialize)]
+pub struct TimeWindowParams {
+    pub since: Option<String>,
+    pub window: Option<String>,
+}
+
+impl TimeWindowParams {
+    // Earliest deck.date_updated (in milliseconds) to include, 0 means all time
+    pub fn updated_since(&self) -> Result<i64, ApiError> {
+        match (&self.since, &self.window) {
+            (Some(_), Some(_)) => Err(ApiError::bad_request(
+                "use either 'since' or 'window', not both",
+            )),
+            (Some(since), None) => parse_since(since),
+            (None, Some(window)) => {
+                let days = match window.as_str() {
+                    "7d" => 7,
+                    "30d" => 30,
+                    "90d" => 90,
+                    "all" => return Ok(0),
+                    _ => {
+                        return Err(ApiError::bad_request(format!(
+                            "invalid window '{window}', expected one of: 7d, 30d, 90d, all"
+                        )))
+                    }
+                };
+                Ok((Utc::now() - chrono::Duration::days(days)).timestamp_millis())
+            }
+            (None, None) => Ok(0),
+        }
+    }
+}
+
+// Accepts a plain date (2024-06-01) or a full RFC 3339 timestamp (2024-06-01T12:00:00Z)
+fn parse_since(since: &str) -> Result<i64, ApiError> {
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is a valid time")
+            .and_utc()
+            .timestamp_millis());
+    }
+
+    DateTime::parse_from_rfc3339(since)
+        .map(|date| date.timestamp_millis())
+        .map_err(|_| {
+            ApiError::bad_request(format!(
+                "invalid since '{since}', expected a date like 2024-06-01"
+            ))
+        })
+}
@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+
+use crate::ResolvedCard;
+
+// A problem with the deck, `card` is None for problems with the deck as a whole (eg. its size)
+#[derive(Debug, serde::Serialize)]
+pub struct DeckProblem {
+    pub card: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DeckValidation {
+    pub is_legal: bool,
+    pub deck_size: i32,
+    pub required_deck_size: i32,
+    pub problems: Vec<DeckProblem>,
+}
+
+impl DeckProblem {
+    fn deck(reason: impl Into<String>) -> Self {
+        DeckProblem {
+            card: None,
+            reason: reason.into(),
+        }
+    }
+
+    fn card(name: &str, reason: impl Into<String>) -> Self {
+        DeckProblem {
+            card: Some(name.to_string()),
+            reason: reason.into(),
+        }
+    }
+}
+
+// Standard Brawl decks are exactly 60 cards including the commander. Historic Brawl on Arena
+// is played with 100 card decks (99 plus the commander), so a 60 card Historic deck would be
+// refused by the import just like an oversized Standard one. Without a format the Standard
+// size is used.
+pub fn required_deck_size(format: Option<&str>) -> i32 {
+    match format {
+        Some("historic") => 100,
+        _ => 60,
+    }
+}
+
+pub fn validate_deck(
+    cards: &[ResolvedCard],
+    unresolved: &[String],
+    format: Option<&str>,
+) -> DeckValidation {
+    let mut problems: Vec<DeckProblem> = unresolved
+        .iter()
+        .map(|name| DeckProblem::card(name, "couldn't find a card with this name"))
+        .collect();
+
+    let is_legal_in_format = |card: &ResolvedCard| match format {
+        Some("standard") => card.card.is_legal_standard,
+        _ => card.card.is_legal,
+    };
+
+    // The companion starts outside the deck, so it doesn't count towards the deck size
+    let deck: Vec<&ResolvedCard> = cards.iter().filter(|card| !card.is_companion).collect();
+    let deck_size: i32 = deck.iter().map(|card| card.quantity).sum();
+    let required_deck_size = required_deck_size(format);
+    if deck_size != required_deck_size {
+        problems.push(DeckProblem::deck(format!(
+            "deck has {deck_size} cards including the commander, it needs exactly {required_deck_size}"
+        )));
+    }
+
+    let commanders: Vec<&ResolvedCard> = cards.iter().filter(|card| card.is_commander).collect();
+    let commander = match commanders.as_slice() {
+        [] => {
+            problems.push(DeckProblem::deck("deck has no commander"));
+            None
+        }
+        [commander] => Some(*commander),
+        [commander, ..] => {
+            problems.push(DeckProblem::deck("deck has more than one commander"));
+            Some(*commander)
+        }
+    };
+
+    if let Some(commander) = commander {
+        if !commander.card.is_legal_commander || !is_legal_in_format(commander) {
+            problems.push(DeckProblem::card(
+                &commander.card.name_full,
+                "can't be your commander",
+            ));
+        }
+    }
+
+    // The same card can be listed more than once, eg. in both the Commander and Deck sections
+    let mut copies: HashMap<&str, (&ResolvedCard, i32)> = HashMap::new();
+    for card in &deck {
+        copies
+            .entry(card.card.oracle_id.as_str())
+            .or_insert((card, 0))
+            .1 += card.quantity;
+    }
+    let mut copies: Vec<(&ResolvedCard, i32)> = copies.into_values().collect();
+    copies.sort_by(|(a, _), (b, _)| a.card.name_full.cmp(&b.card.name_full));
+
+    for (card, quantity) in copies {
+        let max_copies = max_copies(card);
+        if max_copies.is_some_and(|max_copies| quantity > max_copies) {
+            problems.push(DeckProblem::card(
+                &card.card.name_full,
+                format!("deck has {quantity} copies, Brawl decks are singleton"),
+            ));
+        }
+    }
+
+    for card in cards {
+        let name = &card.card.name_full;
+        if !is_legal_in_format(card) {
+            problems.push(DeckProblem::card(name, "isn't legal in Brawl"));
+        }
+
+        if let Some(commander) = commander {
+            if !card.is_commander
+                && !card
+                    .card
+                    .color_identity
+                    .iter()
+                    .all(|color| commander.card.color_identity.contains(color))
+            {
+                problems.push(DeckProblem::card(
+                    name,
+                    format!(
+                        "color identity {{{}}} is outside the commander's color identity {{{}}}",
+                        card.card.color_identity.join(","),
+                        commander.card.color_identity.join(",")
+                    ),
+                ));
+            }
+        }
+    }
+
+    let companions: Vec<&ResolvedCard> = cards.iter().filter(|card| card.is_companion).collect();
+    if companions.len() > 1 {
+        problems.push(DeckProblem::deck("deck has more than one companion"));
+    }
+    for companion in companions {
+        // The commander is part of the starting deck
+        if let Some(reason) = companion_problem(companion, &deck) {
+            problems.push(DeckProblem::card(&companion.card.name_full, reason));
+        }
+    }
+
+    DeckValidation {
+        is_legal: problems.is_empty(),
+        deck_size,
+        required_deck_size,
+        problems,
+    }
+}
+
+// None means any number of copies
+fn max_copies(card: &ResolvedCard) -> Option<i32> {
+    let oracle_text = card.card.oracle_text.as_deref().unwrap_or_default();
+    if card.card.type_line_front.starts_with("Basic")
+        || oracle_text.contains("A deck can have any number of cards named")
+    {
+        None
+    } else if oracle_text.contains("A deck can have up to seven cards named") {
+        Some(7)
+    } else {
+        Some(1)
+    }
+}
+
+fn is_land(card: &ResolvedCard) -> bool {
+    card.card.type_line_front.contains("Land")
+}
+
+fn is_permanent(card: &ResolvedCard) -> bool {
+    [
+        "Artifact",
+        "Battle",
+        "Creature",
+        "Enchantment",
+        "Land",
+        "Planeswalker",
+    ]
+    .iter()
+    .any(|card_type| card.card.type_line_front.contains(card_type))
+}
+
+fn card_types(card: &ResolvedCard) -> Vec<&str> {
+    let types = card
+        .card
+        .type_line_front
+        .split('—')
+        .next()
+        .unwrap_or_default();
+    types
+        .split_whitespace()
+        .filter(|card_type| {
+            !["Legendary", "Basic", "Snow", "World", "Tribal", "Kindred"].contains(card_type)
+        })
+        .collect()
+}
+
+// Checks the companion's deckbuilding condition against the starting deck
+fn companion_problem(companion: &ResolvedCard, deck: &[&ResolvedCard]) -> Option<String> {
+    let oracle_text = companion.card.oracle_text.as_deref().unwrap_or_default();
+    if !oracle_text.starts_with("Companion") {
+        return Some("doesn't have companion".to_string());
+    }
+
+    let nonland: Vec<&&ResolvedCard> = deck.iter().filter(|card| !is_land(card)).collect();
+    let offenders: Vec<&str> = match companion.card.name_front.as_str() {
+        "Lurrus of the Dream-Den" => deck
+            .iter()
+            .filter(|card| is_permanent(card) && card.card.cmc > 2.0)
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Obosh, the Preypiercer" => nonland
+            .iter()
+            .filter(|card| card.card.cmc as i32 % 2 == 0)
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Gyruda, Doom of Depths" => nonland
+            .iter()
+            .filter(|card| card.card.cmc as i32 % 2 == 1)
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Keruga, the Macrosage" => nonland
+            .iter()
+            .filter(|card| card.card.cmc < 3.0)
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Jegantha, the Wellspring" => deck
+            .iter()
+            .filter(|card| {
+                let mana_cost = card.card.mana_cost_front.as_deref().unwrap_or_default();
+                let symbols: Vec<&str> = mana_cost
+                    .split('}')
+                    .filter(|symbol| !symbol.is_empty())
+                    .collect();
+                symbols
+                    .iter()
+                    .enumerate()
+                    .any(|(i, symbol)| symbols[i + 1..].contains(symbol))
+            })
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Kaheera, the Orphanguard" => deck
+            .iter()
+            .filter(|card| {
+                card.card.type_line_front.contains("Creature")
+                    && !["Cat", "Elemental", "Nightmare", "Dinosaur", "Beast"]
+                        .iter()
+                        .any(|creature_type| card.card.type_line_front.contains(creature_type))
+            })
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Umori, the Collector" => {
+            let shared = nonland
+                .first()
+                .map(|card| card_types(card))
+                .unwrap_or_default();
+            let shared: Vec<&str> = shared
+                .into_iter()
+                .filter(|card_type| {
+                    nonland
+                        .iter()
+                        .all(|card| card_types(card).contains(card_type))
+                })
+                .collect();
+            if shared.is_empty() && !nonland.is_empty() {
+                return Some("every nonland card in the deck must share a card type".to_string());
+            }
+            vec![]
+        }
+        "Zirda, the Dawnwaker" => deck
+            .iter()
+            .filter(|card| {
+                is_permanent(card)
+                    && !card
+                        .card
+                        .oracle_text
+                        .as_deref()
+                        .unwrap_or_default()
+                        .contains(':')
+            })
+            .map(|card| card.card.name_full.as_str())
+            .collect(),
+        "Yorion, Sky Nomad" => {
+            return Some(
+                "Brawl decks have an exact size, so Yorion's condition can't be met".to_string(),
+            )
+        }
+        // Lutri's condition is already covered by the singleton rule
+        _ => vec![],
+    };
+
+    if offenders.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "companion condition isn't met by: {}",
+            offenders.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Card;
+
+    fn card(name: &str, type_line: &str, color_identity: &[&str]) -> ResolvedCard {
+        ResolvedCard {
+            card: Card {
+                oracle_id: name.to_string(),
+                name_full: name.to_string(),
+                name_front: name.to_string(),
+                name_back: None,
+                slug: name.to_lowercase().replace(' ', "-"),
+                scryfall_uri: String::new(),
+                layout: "normal".to_string(),
+                rarity: "common".to_string(),
+                lowest_rarity: "common".to_string(),
+                lang: "en".to_string(),
+                mana_cost_combined: None,
+                mana_cost_front: None,
+                mana_cost_back: None,
+                cmc: 1.0,
+                type_line_full: type_line.to_string(),
+                type_line_front: type_line.to_string(),
+                type_line_back: None,
+                oracle_text: None,
+                oracle_text_back: None,
+                colors: None,
+                colors_back: None,
+                color_identity: color_identity
+                    .iter()
+                    .map(|color| color.to_string())
+                    .collect(),
+                is_legal: true,
+                is_legal_standard: true,
+                arena_id: None,
+                is_legal_commander: false,
+                is_rebalanced: false,
+                image_small: String::new(),
+                image_normal: String::new(),
+                image_large: String::new(),
+                image_art_crop: String::new(),
+                image_border_crop: String::new(),
+                image_small_back: None,
+                image_normal_back: None,
+                image_large_back: None,
+                image_art_crop_back: None,
+                image_border_crop_back: None,
+            },
+            quantity: 1,
+            is_commander: false,
+            is_companion: false,
+        }
+    }
+
+    fn commander() -> ResolvedCard {
+        let mut commander = card(
+            "Krenko, Mob Boss",
+            "Legendary Creature — Goblin Warrior",
+            &["R"],
+        );
+        commander.card.is_legal_commander = true;
+        commander.is_commander = true;
+        commander
+    }
+
+    // The commander plus `size - 1` different red cards
+    fn deck(size: usize) -> Vec<ResolvedCard> {
+        let mut cards = vec![commander()];
+        cards.extend((1..size).map(|i| card(&format!("Card {i}"), "Instant", &["R"])));
+        cards
+    }
+
+    fn reasons(validation: &DeckValidation) -> Vec<(Option<&str>, &str)> {
+        validation
+            .problems
+            .iter()
+            .map(|problem| (problem.card.as_deref(), problem.reason.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn standard_deck_needs_60_cards() {
+        let validation = validate_deck(&deck(60), &[], Some("standard"));
+        assert!(validation.is_legal, "{:?}", validation.problems);
+        assert_eq!(validation.deck_size, 60);
+
+        let validation = validate_deck(&deck(100), &[], Some("standard"));
+        assert_eq!(
+            reasons(&validation),
+            [(
+                None,
+                "deck has 100 cards including the commander, it needs exactly 60"
+            )]
+        );
+    }
+
+    #[test]
+    fn historic_deck_needs_100_cards() {
+        let validation = validate_deck(&deck(100), &[], Some("historic"));
+        assert!(validation.is_legal, "{:?}", validation.problems);
+        assert_eq!(validation.required_deck_size, 100);
+
+        let validation = validate_deck(&deck(60), &[], Some("historic"));
+        assert_eq!(
+            reasons(&validation),
+            [(
+                None,
+                "deck has 60 cards including the commander, it needs exactly 100"
+            )]
+        );
+    }
+
+    #[test]
+    fn deck_without_format_uses_the_standard_size() {
+        assert_eq!(required_deck_size(None), 60);
+        assert!(validate_deck(&deck(60), &[], None).is_legal);
+    }
+
+    #[test]
+    fn unresolved_cards_are_problems() {
+        let validation = validate_deck(&deck(60), &["Not A Card".to_string()], None);
+        assert_eq!(
+            reasons(&validation),
+            [(Some("Not A Card"), "couldn't find a card with this name")]
+        );
+    }
+
+    #[test]
+    fn nonbasic_cards_are_singleton() {
+        let mut cards = deck(59);
+        cards.push(card("Card 1", "Instant", &["R"]));
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(
+            reasons(&validation),
+            [(
+                Some("Card 1"),
+                "deck has 2 copies, Brawl decks are singleton"
+            )]
+        );
+    }
+
+    #[test]
+    fn basic_lands_are_not_singleton() {
+        let mut cards = deck(40);
+        let mut mountain = card("Mountain", "Basic Land — Mountain", &[]);
+        mountain.quantity = 20;
+        cards.push(mountain);
+        let validation = validate_deck(&cards, &[], None);
+        assert!(validation.is_legal, "{:?}", validation.problems);
+    }
+
+    #[test]
+    fn cards_can_allow_more_copies() {
+        let mut cards = deck(52);
+        let mut dwarves = card("Seven Dwarves", "Creature — Dwarf", &["R"]);
+        dwarves.card.oracle_text =
+            Some("A deck can have up to seven cards named Seven Dwarves.".to_string());
+        dwarves.quantity = 8;
+        cards.push(dwarves);
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(
+            reasons(&validation),
+            [(
+                Some("Seven Dwarves"),
+                "deck has 8 copies, Brawl decks are singleton"
+            )]
+        );
+    }
+
+    #[test]
+    fn cards_must_be_in_the_commanders_color_identity() {
+        let mut cards = deck(59);
+        cards.push(card("Opt", "Instant", &["U"]));
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(
+            reasons(&validation),
+            [(
+                Some("Opt"),
+                "color identity {U} is outside the commander's color identity {R}"
+            )]
+        );
+    }
+
+    #[test]
+    fn companion_must_be_in_the_commanders_color_identity() {
+        let mut cards = deck(60);
+        let mut lutri = card(
+            "Lutri, the Spellchaser",
+            "Legendary Creature — Elemental Otter",
+            &["U", "R"],
+        );
+        lutri.card.oracle_text = Some(
+            "Companion — Each nonland card in your starting deck has a different name.".to_string(),
+        );
+        lutri.is_companion = true;
+        cards.push(lutri);
+        let validation = validate_deck(&cards, &[], None);
+        // The companion doesn't count towards the deck size
+        assert_eq!(validation.deck_size, 60);
+        assert_eq!(
+            reasons(&validation),
+            [(
+                Some("Lutri, the Spellchaser"),
+                "color identity {U,R} is outside the commander's color identity {R}"
+            )]
+        );
+    }
+
+    #[test]
+    fn companion_condition_is_checked() {
+        let mut cards = deck(60);
+        cards[1].card.cmc = 3.0;
+        cards[1].card.type_line_front = "Creature — Goblin".to_string();
+        let mut lurrus = card(
+            "Lurrus of the Dream-Den",
+            "Legendary Creature — Cat Nightmare",
+            &["R"],
+        );
+        lurrus.card.oracle_text = Some(
+            "Companion — Each permanent card in your starting deck has mana value 2 or less."
+                .to_string(),
+        );
+        lurrus.is_companion = true;
+        cards.push(lurrus);
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(
+            reasons(&validation),
+            [(
+                Some("Lurrus of the Dream-Den"),
+                "companion condition isn't met by: Card 1"
+            )]
+        );
+    }
+
+    #[test]
+    fn non_legendary_commander_is_a_problem() {
+        let mut cards = deck(60);
+        cards[0] = card("Goblin Guide", "Creature — Goblin Scout", &["R"]);
+        cards[0].is_commander = true;
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(
+            reasons(&validation),
+            [(Some("Goblin Guide"), "can't be your commander")]
+        );
+    }
+
+    #[test]
+    fn deck_needs_a_commander() {
+        let mut cards = deck(60);
+        cards[0].is_commander = false;
+        let validation = validate_deck(&cards, &[], None);
+        assert_eq!(reasons(&validation), [(None, "deck has no commander")]);
+    }
+
+    #[test]
+    fn cards_must_be_legal_in_the_format() {
+        let mut cards = deck(60);
+        cards[1].card.is_legal_standard = false;
+        assert!(validate_deck(&cards, &[], None).is_legal);
+        assert_eq!(
+            reasons(&validate_deck(&cards, &[], Some("standard"))),
+            [(Some("Card 1"), "isn't legal in Brawl")]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use serde::Deserialize;
+
+use crate::error::ApiError;
+
+const RARITIES: [&str; 4] = ["common", "uncommon", "rare", "mythic"];
+
+// Anything Arena doesn't sell by the usual rarities (eg. "special") is treated as a mythic
+fn rarity_rank(rarity: &str) -> usize {
+    RARITIES
+        .iter()
+        .position(|r| *r == rarity)
+        .unwrap_or(RARITIES.len() - 1)
+}
+
+// Wildcards needed to craft a list of cards on Arena, each card at the lowest rarity it was printed at
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct WildcardCost {
+    pub common: i32,
+    pub uncommon: i32,
+    pub rare: i32,
+    pub mythic: i32,
+}
+
+impl WildcardCost {
+    // Basic lands, snow ones included, are free, so they don't cost a wildcard
+    pub fn add(&mut self, lowest_rarity: &str, type_line_front: &str, quantity: i32) {
+        if type_line_front.starts_with("Basic") {
+            return;
+        }
+        match rarity_rank(lowest_rarity) {
+            0 => self.common += quantity,
+            1 => self.uncommon += quantity,
+            2 => self.rare += quantity,
+            _ => self.mythic += quantity,
+        }
+    }
+}
+
+// Query parameters for budget lists, eg. `?max_rarity=uncommon` or `?budget=5`
+// `budget` is how many rare and mythic wildcards each page of a list may cost
+#[derive(Debug, Deserialize)]
+pub struct WildcardParams {
+    pub max_rarity: Option<String>,
+    pub budget: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WildcardFilter {
+    max_rarity: Option<usize>,
+    budget: Option<i32>,
+}
+
+impl WildcardParams {
+    pub fn filter(&self) -> Result<WildcardFilter, ApiError> {
+        let max_rarity = match self.max_rarity.as_deref().map(str::to_ascii_lowercase) {
+            None => None,
+            Some(max_rarity) => Some(
+                RARITIES
+                    .iter()
+                    .position(|rarity| *rarity == max_rarity)
+                    .ok_or_else(|| {
+                        ApiError::bad_request(format!(
+                            "invalid max_rarity '{max_rarity}', expected one of: {}",
+                            RARITIES.join(", ")
+                        ))
+                    })?,
+            ),
+        };
+
+        if self.budget.is_some_and(|budget| budget < 0) {
+            return Err(ApiError::bad_request("budget must be 0 or greater"));
+        }
+
+        Ok(WildcardFilter {
+            max_rarity,
+            budget: self.budget,
+        })
+    }
+}
+
+impl WildcardFilter {
+    // Keeps `cards` in order, every `per_page` cards of the result hold at most `budget` rares and mythics
+    // so the budget holds for any page of the list, the ones that don't fit are left out
+    pub fn apply<T>(
+        &self,
+        cards: Vec<T>,
+        per_page: usize,
+        lowest_rarity: impl Fn(&T) -> &str,
+    ) -> Vec<T> {
+        let mut kept: Vec<T> = vec![];
+        let (mut on_page, mut spent) = (0, 0);
+        for card in cards {
+            let rank = rarity_rank(lowest_rarity(&card));
+            if self.max_rarity.is_some_and(|max_rarity| rank > max_rarity) {
+                continue;
+            }
+            if on_page == per_page {
+                on_page = 0;
+                spent = 0;
+            }
+            if rank >= 2 {
+                if self.budget.is_some_and(|budget| spent >= budget) {
+                    continue;
+                }
+                spent += 1;
+            }
+            on_page += 1;
+            kept.push(card);
+        }
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(max_rarity: Option<&str>, budget: Option<i32>) -> WildcardFilter {
+        WildcardParams {
+            max_rarity: max_rarity.map(str::to_string),
+            budget,
+        }
+        .filter()
+        .unwrap()
+    }
+
+    fn apply(filter: WildcardFilter, cards: &[&'static str], per_page: usize) -> Vec<&'static str> {
+        filter.apply(cards.to_vec(), per_page, |card| card)
+    }
+
+    #[test]
+    fn keeps_everything_without_a_filter() {
+        let cards = ["mythic", "common", "rare", "uncommon"];
+        assert_eq!(apply(filter(None, None), &cards, 2), cards);
+    }
+
+    #[test]
+    fn max_rarity_leaves_out_higher_rarities() {
+        assert_eq!(
+            apply(
+                filter(Some("uncommon"), None),
+                &["mythic", "common", "special", "uncommon"],
+                10
+            ),
+            vec!["common", "uncommon"]
+        );
+    }
+
+    #[test]
+    fn budget_applies_to_every_page() {
+        let cards = [
+            "rare", "mythic", "common", "rare", "common", "rare", "mythic", "common",
+        ];
+        // Pages of 3 with one rare or mythic each: [rare, common, common] [rare, common]
+        assert_eq!(
+            apply(filter(None, Some(1)), &cards, 3),
+            vec!["rare", "common", "common", "rare", "common"]
+        );
+    }
+
+    #[test]
+    fn zero_budget_leaves_only_commons_and_uncommons() {
+        assert_eq!(
+            apply(
+                filter(None, Some(0)),
+                &["rare", "uncommon", "mythic", "common"],
+                1
+            ),
+            vec!["uncommon", "common"]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        assert!(WildcardParams {
+            max_rarity: Some("special".to_string()),
+            budget: None
+        }
+        .filter()
+        .is_err());
+        assert!(WildcardParams {
+            max_rarity: None,
+            budget: Some(-1)
+        }
+        .filter()
+        .is_err());
+    }
+}